@@ -1,16 +1,50 @@
-use std::{cell::RefCell, ffi::OsString, fs::read_to_string, path::Path, rc::Rc, time::Instant};
+use std::{
+    cell::RefCell,
+    ffi::OsString,
+    fs::{read_to_string, write},
+    path::Path,
+    process::ExitCode,
+    rc::Rc,
+    time::Instant,
+};
 
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use colored::Colorize;
 use limesherbet::{
     backend::vm::VirtualMachine,
     cli_helper::Diagnostics,
     common::{debug::dissasemble_chunk, interner::StringInterner, value::Value},
-    frontend::compiler::{Compiler, FunctionType},
+    formatter,
+    frontend::{
+        ast::dump::AstTree,
+        compiler::{directives::Directives, Compiler, FunctionType},
+        parser,
+        scanner::{Scanner, TokenKind},
+        span::LineIndex,
+    },
+    lsp, test_runner,
 };
 
-fn main() {
+fn main() -> ExitCode {
     let cli = Cli::parse();
-    let path = Path::new(&cli.path);
+    match cli.command {
+        Some(Command::Test { path }) => test(Path::new(&path)),
+        Some(Command::Fmt { paths, check }) => fmt(&paths, check),
+        Some(Command::Lsp) => lsp(),
+        None => {
+            let Some(path) = cli.path else {
+                eprintln!("expected a file to run, see --help");
+                return ExitCode::FAILURE;
+            };
+            match cli.emit {
+                Some(emit) => self::emit(Path::new(&path), emit),
+                None => run(Path::new(&path), cli.display_bytecode),
+            }
+        }
+    }
+}
+
+fn run(path: &Path, display_bytecode: bool) -> ExitCode {
     let source = read_to_string(path).unwrap();
 
     let interner = Rc::new(RefCell::new(StringInterner::default()));
@@ -26,15 +60,177 @@ fn main() {
         "took {}s to compile to bytecode",
         start.elapsed().as_secs_f64()
     );
-    if cli.display_bytecode {
+    if display_bytecode {
         dissasemble_chunk(&compiled.chunk, "main");
     }
     let interner = Rc::try_unwrap(interner).unwrap().into_inner();
     let mut vm = VirtualMachine::new(interner);
 
     vm.stack.push(Value::Void);
-    vm.call(&compiled, 0);
-    vm.run();
+
+    let start = Instant::now();
+    let result = vm.call(&compiled, 0).and_then(|()| vm.run());
+    println!("vm took {}", start.elapsed().as_secs_f64());
+    if let Err(err) = result {
+        eprintln!("{} {}:{err}", "error:".red().bold(), path.display());
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// prints what a phase of the compiler made of the file and stops there
+fn emit(path: &Path, emit: Emit) -> ExitCode {
+    let source = read_to_string(path).unwrap();
+    match emit {
+        Emit::Tokens => {
+            let lines = LineIndex::new(&source);
+            let mut scanner = Scanner::new(source);
+            let mut failed = false;
+            for token in scanner.scan_thru() {
+                let position = lines.position(token.span.start);
+                failed |= token.kind == TokenKind::Error;
+                println!(
+                    "{}:{}\t{:?}\t{:?}",
+                    position.line + 1,
+                    position.column + 1,
+                    token.kind,
+                    scanner.lexeme(&token)
+                );
+            }
+            if failed {
+                return ExitCode::FAILURE;
+            }
+        }
+        Emit::Ast | Emit::AstJson => {
+            let lines = LineIndex::new(&source);
+            let mut parser = parser::Parser::new(
+                Scanner::new(source),
+                create_rc(Diagnostics::new(path)),
+                FunctionType::Script,
+                Directives::builtin(),
+            );
+            let file = parser.parse_file();
+            if parser.had_error {
+                return ExitCode::FAILURE;
+            }
+            let tree = AstTree::from_nodes(&file.nodes);
+            if emit == Emit::Ast {
+                print!("{}", tree.to_text(&lines));
+            } else {
+                println!("{:#}", tree.to_json());
+            }
+        }
+        Emit::Bytecode => {
+            let interner = Rc::new(RefCell::new(StringInterner::default()));
+            let diagnostics = create_rc(Diagnostics::new(path));
+            let compiler = Compiler::new(interner, diagnostics, FunctionType::Script);
+            let Ok(compiled) = compiler.compile(source) else {
+                return ExitCode::FAILURE;
+            };
+            dissasemble_chunk(&compiled.chunk, "main");
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn test(path: &Path) -> ExitCode {
+    let (mut passed, mut failed) = (0, 0);
+    for file in test_runner::discover(path) {
+        let report = match test_runner::run_file(&file) {
+            Ok(report) => report,
+            Err(err) => {
+                println!("{} {err}", "error:".red().bold());
+                failed += 1;
+                continue;
+            }
+        };
+        if report.outcomes.is_empty() {
+            continue;
+        }
+        println!(
+            "running {} tests in {}",
+            report.outcomes.len(),
+            file.display()
+        );
+        for outcome in &report.outcomes {
+            match &outcome.failure {
+                None => println!("test {} ... {}", outcome.name, "ok".green()),
+                Some(_) => println!("test {} ... {}", outcome.name, "FAILED".red()),
+            }
+        }
+        for outcome in report.failed() {
+            println!(
+                "\n---- {} ({}:{}) ----\n{}",
+                outcome.name,
+                file.display(),
+                outcome.line + 1,
+                outcome.failure.as_ref().unwrap()
+            );
+        }
+        passed += report.passed();
+        failed += report.failed().count();
+        println!();
+    }
+
+    let result = if failed == 0 {
+        "ok".green()
+    } else {
+        "FAILED".red()
+    };
+    println!("test result: {result}. {passed} passed; {failed} failed");
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// rewrites every file in place, or with `check` only lists the ones that
+/// aren't formatted
+fn fmt(paths: &[OsString], check: bool) -> ExitCode {
+    let mut failed = false;
+    for file in paths
+        .iter()
+        .flat_map(|path| test_runner::discover(Path::new(path)))
+    {
+        let source = read_to_string(&file).unwrap();
+        let formatted = match formatter::format_source(&file, &source) {
+            Ok(formatted) => formatted,
+            Err(_) => {
+                // the parser already reported why
+                failed = true;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+        if check {
+            println!("{} is not formatted", file.display());
+            failed = true;
+        } else if let Err(err) = write(&file, formatted) {
+            eprintln!("{} {}: {err}", "error:".red().bold(), file.display());
+            failed = true;
+        }
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// serves the language server over stdio until the editor closes it
+fn lsp() -> ExitCode {
+    let stdin = std::io::stdin();
+    match lsp::serve(stdin.lock(), std::io::stdout().lock()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("{} {err}", "error:".red().bold());
+            ExitCode::FAILURE
+        }
+    }
 }
 
 pub type RcRf<T> = Rc<RefCell<T>>;
@@ -43,11 +239,49 @@ pub fn create_rc<T>(v: T) -> RcRf<T> {
     Rc::new(RefCell::new(v))
 }
 #[derive(Parser)]
-#[clap(author, version, about)]
+#[clap(author, version, about, args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[clap(value_parser)]
-    path: OsString,
+    path: Option<OsString>,
 
     #[arg(long = "dbc", help = "Displays the compiled bytecode")]
     display_bytecode: bool,
+
+    /// Prints the output of a compiler phase instead of running the file
+    #[arg(long, value_enum)]
+    emit: Option<Emit>,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Emit {
+    /// The token stream from the scanner
+    Tokens,
+    /// The parsed tree, indented
+    Ast,
+    /// The parsed tree as json
+    AstJson,
+    /// The disassembled bytecode
+    Bytecode,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Runs the `test` blocks of a file, or of every file in a directory
+    Test {
+        #[clap(value_parser)]
+        path: OsString,
+    },
+    /// Formats files, or every file in a directory, in place
+    Fmt {
+        #[clap(value_parser, required = true)]
+        paths: Vec<OsString>,
+        /// Only lists the files that aren't formatted, failing if there are any
+        #[arg(long)]
+        check: bool,
+    },
+    /// Runs a language server over stdin and stdout, for editors
+    Lsp,
 }
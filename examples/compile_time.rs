@@ -0,0 +1,57 @@
+//! compiles a large generated script and prints how long it took, run with
+//! `cargo run --release --example compile_time [functions]`
+use std::{cell::RefCell, fmt::Write, path::Path, rc::Rc, time::Instant};
+
+use limesherbet::{
+    cli_helper::Diagnostics,
+    common::interner::StringInterner,
+    frontend::{
+        compiler::{directives::Directives, Compiler, FunctionType},
+        parser::Parser,
+        scanner::Scanner,
+    },
+};
+
+fn main() {
+    let functions: usize = std::env::args()
+        .nth(1)
+        .map_or(20_000, |count| count.parse().unwrap());
+
+    let mut source = String::new();
+    for i in 0..functions {
+        writeln!(
+            source,
+            "func function_{i}(a, b) {{\n    let sum = a + b * {i};\n    \
+             if sum > 10 {{ return sum - 1; }} else {{ return \"small\"; }}\n}}\n\
+             let value_{i} = function_{i}({i}, 2);"
+        )
+        .unwrap();
+    }
+
+    let diagnostics = Rc::new(RefCell::new(Diagnostics::new(Path::new("generated.mng"))));
+
+    let start = Instant::now();
+    let mut parser = Parser::new(
+        Scanner::new(source.clone()),
+        diagnostics.clone(),
+        FunctionType::Script,
+        Directives::builtin(),
+    );
+    parser.parse_file();
+    println!(
+        "parsed {} KiB ({functions} functions) in {:.3}s",
+        source.len() / 1024,
+        start.elapsed().as_secs_f64()
+    );
+
+    let interner = Rc::new(RefCell::new(StringInterner::default()));
+    let compiler = Compiler::new(interner, diagnostics, FunctionType::Script);
+
+    let start = Instant::now();
+    compiler.compile(source.clone()).unwrap();
+    println!(
+        "compiled {} KiB ({functions} functions) in {:.3}s",
+        source.len() / 1024,
+        start.elapsed().as_secs_f64()
+    );
+}
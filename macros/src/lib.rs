@@ -1,14 +1,9 @@
 use proc_macro::{self, TokenStream};
 
-use syn::Arm;
-
-struct Arms {
-    arms: Vec<Arm>,
-}
 // the ground work for adding a better "api" for manipulating opcodes
 mod opcode;
 use opcode::expand_opcode as _expand_opcode;
-#[proc_macro_derive(ExpandOpCode)]
+#[proc_macro_derive(ExpandOpCode, attributes(binary_op))]
 pub fn expand_opcode(input: TokenStream) -> TokenStream {
     _expand_opcode(input)
 }
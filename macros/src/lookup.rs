@@ -11,6 +11,7 @@ pub fn lookup(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     quote! {}.into()
 }
 
+#[allow(dead_code)]
 pub struct Definitions(pub Vec<Definition>);
 impl Parse for Definitions {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
@@ -24,6 +25,7 @@ impl Parse for Definitions {
         Ok(Definitions(definitions))
     }
 }
+#[allow(dead_code)]
 pub struct Definition {
     pub ident: Ident,
     pub colon: Colon,
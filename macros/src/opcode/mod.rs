@@ -3,8 +3,8 @@ mod attributes;
 use proc_macro2::{Ident, TokenStream};
 use quote::quote;
 use syn::{
-    parse_macro_input, punctuated::Punctuated, spanned::Spanned, token::Comma, Data, DataEnum,
-    DeriveInput, Field, Fields, Variant,
+    parse_macro_input, punctuated::Punctuated, spanned::Spanned, DeriveInput, Field, Fields,
+    Variant,
 };
 pub fn expand_opcode(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     // Parse the input tokens into a syntax tree
@@ -16,3 +16,14 @@ impl CallFrame {
         }
     }
 }
+
+/// an active `try` block
+#[derive(Debug, Clone)]
+pub struct Handler {
+    /// the frame the `try` is in
+    pub frame_count: usize,
+    /// the stack is truncated back to this before jumping to the `catch`
+    pub stack_len: usize,
+    /// where the `catch` block starts
+    pub catch_ip: usize,
+}
@@ -0,0 +1,60 @@
+use std::fmt::{self, Display};
+
+use crate::common::value::Value;
+
+use super::ops::describe_value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    /// raised by the vm itself, like calling a number
+    Runtime,
+    /// `throw value`
+    Thrown(Value),
+    /// a failed assertion, `try` never catches these
+    Assertion,
+}
+
+/// an error raised while running a script, like a failed assertion
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub message: String,
+    /// line and column, both starting at 0
+    pub location: Option<(usize, usize)>,
+    pub kind: ErrorKind,
+}
+
+impl RuntimeError {
+    pub fn new(message: String) -> RuntimeError {
+        RuntimeError {
+            message,
+            location: None,
+            kind: ErrorKind::Runtime,
+        }
+    }
+    pub fn at(message: String, line: usize, column: usize) -> RuntimeError {
+        RuntimeError {
+            message,
+            location: Some((line, column)),
+            kind: ErrorKind::Runtime,
+        }
+    }
+    pub fn thrown(value: Value) -> RuntimeError {
+        RuntimeError {
+            message: format!("uncaught error: {}", describe_value(&value)),
+            location: None,
+            kind: ErrorKind::Thrown(value),
+        }
+    }
+    pub fn catchable(&self) -> bool {
+        self.kind != ErrorKind::Assertion
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some((line, column)) => write!(f, "{}:{}: {}", line + 1, column + 1, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
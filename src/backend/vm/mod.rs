@@ -1,24 +1,46 @@
-use std::{collections::HashMap, time::Instant};
+use std::{collections::HashMap, rc::Rc};
+
+use lazy_static::lazy_static;
 
 use crate::common::{
-    chunk::Chunk,
+    chunk::{AssertionInfo, Chunk},
+    class::{BoundMethod, Class, Instance},
+    enums::Tagged,
+    error::ErrorValue,
     function::Function,
-    interner::StringInterner,
+    interner::{InternedString, StringInterner},
     natives::Native,
     opcode::OpCode,
-    value::{AsValue, Value},
+    value::{rcrf, AsValue, Ptr, Value},
+};
+
+use self::{
+    error::{ErrorKind, RuntimeError},
+    ops::{arithmetic, compare_numbers, describe_value, Arithmetic},
+    output::Output,
 };
 
-use super::callframe::CallFrame;
+use super::callframe::{CallFrame, Handler};
 
+pub mod error;
 pub mod natives;
 pub mod ops;
+pub mod output;
+lazy_static! {
+    /// the interned name of the initializer method
+    static ref INIT: usize = InternedString::from("init").0;
+}
 pub const FUNCTION: Function = Function {
     chunk: Chunk {
         code: Vec::new(),
         constants: Vec::new(),
+        assertions: Vec::new(),
+        locations: Vec::new(),
     },
     arity: 0,
+    required: 0,
+    variadic: false,
+    parameters: Vec::new(),
     name: String::new(),
 };
 
@@ -27,9 +49,15 @@ pub struct VirtualMachine {
     pub stack: Vec<Value>,
     pub callframes: [CallFrame; 2048],
     pub frame_count: usize,
+    /// the `try` blocks that are currently running, innermost last
+    pub handlers: Vec<Handler>,
     pub globals: HashMap<usize, Value>,
     pub natives: Vec<Native>,
     pub interner: StringInterner,
+    pub output: Output,
+    /// where the instruction running in the innermost frame is, errors that
+    /// don't say where they happened point at it
+    instruction: usize,
 }
 
 impl VirtualMachine {
@@ -45,27 +73,240 @@ impl VirtualMachine {
             natives: vec![(Native(|_: &[Value], vm: _| println!("stack dump: {:?}", vm.stack)))],
             globals: HashMap::new(),
             frame_count: 0,
+            handlers: Vec::new(),
             interner,
+            output: Output::Stdout,
+            instruction: 0,
         }
     }
+    /// pushes a frame for `function`, the arguments are already on the stack,
+    /// errors once every frame is in use
     #[allow(clippy::not_unsafe_ptr_arg_deref, unsafe_code)]
-    pub fn call(&mut self, function: *const Function, arg_count: usize) {
-        let arity = unsafe { (*function).arity };
-        if arg_count != arity as usize {
-            panic!(
-                "mismatched argument counts! expected {} got {arg_count}",
-                arity
-            )
-        }
+    pub fn call(
+        &mut self,
+        function: *const Function,
+        arg_count: usize,
+    ) -> Result<(), RuntimeError> {
+        let slot_count = unsafe { (*function).slot_count() };
+        assert_eq!(arg_count, slot_count, "arity is checked by call_function");
 
-        let frame = &mut self.callframes[self.frame_count];
+        let Some(frame) = self.callframes.get_mut(self.frame_count) else {
+            return Err(RuntimeError::new(format!(
+                "stack overflow, calls can only nest {} deep",
+                self.callframes.len()
+            )));
+        };
         frame.function = function;
+        frame.ip = 0;
         frame.slots = self.stack.len() - (arg_count + 1);
 
         self.frame_count += 1;
+        Ok(())
     }
-    pub fn run(mut self) {
-        let start = Instant::now();
+    /// like [`VirtualMachine::call`], but the arguments are checked and lined
+    /// up with the parameters first, `names` are the interned names of the
+    /// last arguments when they were passed by name
+    pub fn call_function(
+        &mut self,
+        function: &Ptr<Function>,
+        arg_count: usize,
+        names: &[usize],
+    ) -> Result<(), RuntimeError> {
+        let callee = function.borrow();
+        let arity = callee.arity as usize;
+        if names.is_empty() && arg_count == arity && !callee.variadic {
+            drop(callee);
+            return self.call(function.as_ptr(), arg_count);
+        }
+
+        let positional = arg_count - names.len();
+        if positional > arity && !callee.variadic {
+            return Err(arity_error(&callee, arg_count));
+        }
+        let mut arguments = self.stack.split_off(self.stack.len() - arg_count);
+        let named = arguments.split_off(positional);
+        let rest = arguments.split_off(positional.min(arity));
+        // `None` marks the arguments that weren't given, the function fills
+        // in their defaults
+        arguments.resize(arity, Value::None);
+        for (name, value) in names.iter().zip(named) {
+            let name = self.interned(*name);
+            let Some(index) = callee.parameters[..arity]
+                .iter()
+                .position(|parameter| *parameter == name)
+            else {
+                return Err(RuntimeError::new(format!(
+                    "`{}` has no parameter named `{name}`",
+                    callee.name
+                )));
+            };
+            if !matches!(arguments[index], Value::None) {
+                return Err(RuntimeError::new(format!(
+                    "`{}` was given the argument `{name}` twice",
+                    callee.name
+                )));
+            }
+            arguments[index] = value;
+        }
+        let required = callee.required as usize;
+        if let Some(missing) = arguments[..required]
+            .iter()
+            .position(|argument| matches!(argument, Value::None))
+        {
+            return Err(if names.is_empty() {
+                arity_error(&callee, arg_count)
+            } else {
+                RuntimeError::new(format!(
+                    "`{}` is missing the argument `{}`",
+                    callee.name, callee.parameters[missing]
+                ))
+            });
+        }
+
+        self.stack.extend(arguments);
+        if callee.variadic {
+            self.stack.push(Value::Array(rcrf(rest)));
+        }
+        let slot_count = callee.slot_count();
+        drop(callee);
+        self.call(function.as_ptr(), slot_count)
+    }
+    /// calls whatever sits below the arguments, returns whether a new frame
+    /// was pushed (creating an instance without an `init` doesn't need one)
+    pub fn call_value(&mut self, arg_count: usize, names: &[usize]) -> Result<bool, RuntimeError> {
+        let callee_slot = self.stack.len() - (1 + arg_count);
+        match self.stack[callee_slot].clone() {
+            Value::Function(function) => {
+                self.call_function(&function, arg_count, names)?;
+                Ok(true)
+            }
+            Value::BoundMethod(bound) => {
+                let bound = bound.borrow();
+                self.stack[callee_slot] = bound.receiver.clone();
+                self.call_function(&bound.method, arg_count, names)?;
+                Ok(true)
+            }
+            Value::Class(class) => {
+                let instance = Value::Instance(rcrf(Instance::new(class.clone())));
+                self.stack[callee_slot] = instance;
+                match class.borrow().methods.get(&INIT) {
+                    Some(init) => {
+                        self.call_function(init, arg_count, names)?;
+                        Ok(true)
+                    }
+                    None if arg_count != 0 => Err(RuntimeError::new(format!(
+                        "{} has no init but was given {arg_count} argument(s)",
+                        class.borrow().name
+                    ))),
+                    None => Ok(false),
+                }
+            }
+            Value::Variant(..) if !names.is_empty() => Err(RuntimeError::new(String::from(
+                "enum variants only take positional fields",
+            ))),
+            Value::Variant(enumeration, tag) => {
+                let field_count = enumeration.borrow().variants[tag].fields.len();
+                if arg_count != field_count {
+                    let enumeration = enumeration.borrow();
+                    return Err(RuntimeError::new(format!(
+                        "{}.{} has {field_count} field(s) but was given {arg_count}",
+                        enumeration.name, enumeration.variants[tag].name,
+                    )));
+                }
+                let fields = self.stack.split_off(callee_slot + 1);
+                self.stack[callee_slot] = Value::Tagged(rcrf(Tagged {
+                    enumeration,
+                    tag,
+                    fields,
+                }));
+                Ok(false)
+            }
+            value => Err(RuntimeError::new(format!(
+                "{} is not callable",
+                describe_value(&value)
+            ))),
+        }
+    }
+    /// `receiver.name(args)` without creating a bound method first
+    pub fn invoke(&mut self, name: usize, arg_count: usize) -> Result<bool, RuntimeError> {
+        let receiver_slot = self.stack.len() - (1 + arg_count);
+        let Value::Instance(instance) = self.stack[receiver_slot].clone() else {
+            // `Shape.Circle(1)` or calling a field of anything else
+            self.stack[receiver_slot] =
+                get_property(&self.stack[receiver_slot], InternedString(name))?;
+            return self.call_value(arg_count, &[]);
+        };
+        let instance = instance.borrow();
+        // a field holding something callable shadows the method
+        if let Some(field) = instance.fields.get(&name) {
+            self.stack[receiver_slot] = field.clone();
+            return self.call_value(arg_count, &[]);
+        }
+        let class = instance.class.borrow();
+        let Some(method) = class.methods.get(&name) else {
+            return Err(RuntimeError::new(format!(
+                "<{} instance> has no method `{}`",
+                class.name,
+                self.interned(name)
+            )));
+        };
+        self.call_function(method, arg_count, &[])?;
+        Ok(true)
+    }
+    fn interned(&self, name: usize) -> String {
+        InternedString(name).into()
+    }
+    /// runs until the outermost frame returns, errors that no `try` catches
+    /// are handed back
+    pub fn run(&mut self) -> Result<(), RuntimeError> {
+        loop {
+            match self.execute() {
+                Ok(()) => return Ok(()),
+                Err(error) => self.unwind(self.locate(error))?,
+            }
+        }
+    }
+    /// points an error at the instruction that raised it
+    #[allow(unsafe_code)]
+    fn locate(&self, mut error: RuntimeError) -> RuntimeError {
+        if error.location.is_none() {
+            let function = unsafe { &*self.callframes[self.frame_count - 1].function };
+            error.location = function.chunk.locations[self.instruction];
+        }
+        error
+    }
+    /// jumps to the innermost `catch` with the error on the stack, gives the
+    /// error back if nothing catches it
+    fn unwind(&mut self, error: RuntimeError) -> Result<(), RuntimeError> {
+        if !error.catchable() {
+            return Err(error);
+        }
+        let Some(handler) = self.handlers.pop() else {
+            return Err(error);
+        };
+        let value = match error.kind {
+            ErrorKind::Thrown(value) => value,
+            _ => Value::Error(rcrf(ErrorValue {
+                message: error.message,
+                trace: self.trace(),
+            })),
+        };
+        self.frame_count = handler.frame_count;
+        self.stack.truncate(handler.stack_len);
+        self.stack.push(value);
+        self.callframes[self.frame_count - 1].ip = handler.catch_ip;
+        Ok(())
+    }
+    /// the names of the running functions, innermost first
+    #[allow(unsafe_code)]
+    fn trace(&self) -> Vec<String> {
+        self.callframes[..self.frame_count]
+            .iter()
+            .rev()
+            .map(|frame| unsafe { (*frame.function).name.clone() })
+            .collect()
+    }
+    fn execute(&mut self) -> Result<(), RuntimeError> {
         let mut current_frame = &self.callframes[self.frame_count - 1];
         macro_rules! read_current_frame_fn {
             () => {{
@@ -97,19 +338,10 @@ impl VirtualMachine {
             }};
         }
         macro_rules! binary_op {
-            ($op:tt) => {{
+            ($op:expr) => {{
                 let rhs = pop!();
                 let lhs = pop!();
-
-                match lhs {
-                    Value::Number(lhs) => {
-                        let Value::Number(rhs) = rhs else {
-                            panic!()
-                        };
-                        self.stack.push(Value::Number(lhs $op rhs))
-                    }
-                    x => unimplemented!("cannot apply binary operation to value {}", x),
-                }
+                self.stack.push(arithmetic($op, &lhs, &rhs)?);
             }};
         }
         macro_rules! binary_op_bool {
@@ -117,14 +349,12 @@ impl VirtualMachine {
                 let rhs = pop!();
                 let lhs = pop!();
 
-                match lhs {
-                    Value::Number(lhs) => {
-                        let Value::Number(rhs) = rhs else {
-                            panic!()
-                        };
-                        self.stack.push(Value::Boolean(lhs $op rhs))
-                    }
-                    x => unimplemented!("cannot apply binary operation to value {}", x),
+                match compare_numbers(&lhs, &rhs) {
+                    // comparing with NaN is always false
+                    Some(ordering) => self.stack.push(Value::Boolean(
+                        ordering.is_some_and(|ordering| ordering $op ::std::cmp::Ordering::Equal),
+                    )),
+                    None => return Err(operands_error(stringify!($op), &lhs, &rhs)),
                 }
             }};
         }
@@ -135,20 +365,22 @@ impl VirtualMachine {
             // print!("{ip} Executing ");
             // diassasemble_instruction(ip, instruction, &function.chunk);
             // }
+            self.instruction = ip;
             ip += 1;
 
             match instruction.clone() {
                 OpCode::SetLocalConsumes(index) => {
-                    self.stack[index as usize + 1 + current_frame.slots] = {
-                        let tmp = self.stack.len() - 1;
-                        std::mem::take(&mut self.stack[tmp])
-                    };
+                    self.stack[index as usize + 1 + current_frame.slots] = pop!();
                 }
                 OpCode::Equal => {
-                    binary_op_bool!(==)
+                    let rhs = pop!();
+                    let lhs = pop!();
+                    self.stack.push(Value::Boolean(lhs == rhs));
                 }
                 OpCode::NotEqual => {
-                    binary_op_bool!(!=)
+                    let rhs = pop!();
+                    let lhs = pop!();
+                    self.stack.push(Value::Boolean(lhs != rhs));
                 }
                 OpCode::CallNativeArgPtr(_, _) => {
                     todo!();
@@ -159,22 +391,19 @@ impl VirtualMachine {
                 OpCode::CallNative(location) => {
                     let native = &self.natives[location as usize];
                     let args = [];
-                    (native.0)(&args, &self);
+                    (native.0)(&args, self);
                 }
                 OpCode::JumpTo(offset) => {
                     ip = offset;
                 }
                 OpCode::PopJumpToIfFalse(offset) => {
-                    let popped = pop!();
-                    let condition = popped.as_bool();
-                    if !condition {
+                    if !condition(&pop!())? {
                         ip = offset;
                     }
                 }
 
                 OpCode::JumpToIfFalse(offset) => {
-                    let condition = self.stack[self.stack.len() - 1].as_bool();
-                    if !condition {
+                    if !condition(&self.stack[self.stack.len() - 1])? {
                         ip = offset;
                     }
                 }
@@ -184,15 +413,26 @@ impl VirtualMachine {
                     if let Value::Boolean(bool) = pop {
                         self.stack.push((!bool).to_value());
                     } else {
-                        panic!("not cannot be applied to {} ", pop)
+                        return Err(RuntimeError::new(format!(
+                            "`!` cannot be applied to {}",
+                            describe_value(&pop)
+                        )));
                     }
                 }
                 OpCode::Negate => {
                     let pop = pop!();
                     if let Value::Number(num) = pop {
                         self.stack.push((-num).to_value());
+                    } else if let Value::Int(int) = pop {
+                        let Some(negated) = int.checked_neg() else {
+                            return Err(RuntimeError::new(format!("integer overflow in `-{int}`")));
+                        };
+                        self.stack.push(negated.to_value());
                     } else {
-                        panic!("negate cannot be applied to {} ", pop)
+                        return Err(RuntimeError::new(format!(
+                            "`-` cannot be applied to {}",
+                            describe_value(&pop)
+                        )));
                     }
                 }
                 OpCode::True => self.stack.push(Value::Boolean(true)),
@@ -213,7 +453,13 @@ impl VirtualMachine {
                 }
                 OpCode::GetGlobal(name) => {
                     let name = chunk.constants[name as usize].as_string();
-                    self.stack.push(self.globals.get(&name.0).unwrap().clone())
+                    let Some(value) = self.globals.get(&name.0) else {
+                        return Err(RuntimeError::new(format!(
+                            "undefined variable `{}`",
+                            String::from(*name)
+                        )));
+                    };
+                    self.stack.push(value.clone())
                 }
                 OpCode::SetGlobal(name) => {
                     let name = chunk.constants[name as usize].as_string();
@@ -231,31 +477,21 @@ impl VirtualMachine {
                     let rhs = pop!();
                     let lhs = pop!();
 
-                    match lhs {
-                        Value::Number(lhs) => {
-                            let Value::Number(rhs) = rhs else {
-                                panic!()
-                            };
-                            self.stack.push(Value::Number(lhs + rhs))
-                        }
-                        Value::String(lhs) => {
-                            let Value::String(rhs) = rhs else {
-                                panic!("lhs {:?}\nrhs{:?}",lhs,rhs);
-                            };
-
-                            let mut lhs: String = lhs.into();
-                            let rhs: String = rhs.into();
+                    match (&lhs, &rhs) {
+                        (Value::String(lhs), Value::String(rhs)) => {
+                            let mut lhs: String = (*lhs).into();
+                            let rhs: String = (*rhs).into();
                             lhs.push_str(rhs.as_str());
                             self.stack.push(lhs.to_value());
                         }
-                        _ => unimplemented!(),
+                        _ => self.stack.push(arithmetic(Arithmetic::Add, &lhs, &rhs)?),
                     }
                 }
                 OpCode::Sub => {
-                    binary_op!(-)
+                    binary_op!(Arithmetic::Sub)
                 }
                 OpCode::Mul => {
-                    binary_op!(*)
+                    binary_op!(Arithmetic::Mul)
                 }
                 OpCode::Pop => {
                     #[allow(unsafe_code)]
@@ -263,32 +499,178 @@ impl VirtualMachine {
                         self.stack.set_len(self.stack.len() - 1);
                     }
                 }
-                OpCode::Div => {
-                    binary_op!(/)
-                }
+                OpCode::Div => binary_op!(Arithmetic::Div),
+                OpCode::IntDiv => binary_op!(Arithmetic::IntDiv),
+                OpCode::Rem => binary_op!(Arithmetic::Rem),
+                OpCode::BitAnd => binary_op!(Arithmetic::BitAnd),
+                OpCode::BitOr => binary_op!(Arithmetic::BitOr),
+                OpCode::BitXor => binary_op!(Arithmetic::BitXor),
+                OpCode::Shl => binary_op!(Arithmetic::Shl),
+                OpCode::Shr => binary_op!(Arithmetic::Shr),
                 OpCode::Print => {
-                    println!("{}", pop!());
+                    let value = pop!();
+                    if let Value::Void | Value::None = value {
+                        return Err(RuntimeError::new(format!(
+                            "can't print {}, the expression has no value",
+                            describe_value(&value)
+                        )));
+                    }
+                    self.output.print_line(value);
                 }
-                OpCode::AssertEq => {
+                OpCode::AssertEq(info) | OpCode::AssertNe(info) => {
+                    let info = &chunk.assertions[info as usize];
+                    let message = if info.has_message { Some(pop!()) } else { None };
                     let rhs = pop!();
                     let lhs = pop!();
 
-                    assert_eq!(lhs, rhs);
+                    let expected_equal = matches!(instruction, OpCode::AssertEq(_));
+                    if (lhs == rhs) != expected_equal {
+                        return Err(assertion_failed(info, message, Some((&lhs, &rhs))));
+                    }
+                }
+                OpCode::Assert(info) => {
+                    let info = &chunk.assertions[info as usize];
+                    let message = if info.has_message { Some(pop!()) } else { None };
+                    match pop!() {
+                        Value::Boolean(true) => {}
+                        Value::Boolean(false) => {
+                            return Err(assertion_failed(info, message, None));
+                        }
+                        value => {
+                            return Err(RuntimeError::at(
+                                format!(
+                                    "assert expects a boolean, `{}` is {}",
+                                    info.source,
+                                    describe_value(&value)
+                                ),
+                                info.line,
+                                info.column,
+                            ))
+                        }
+                    }
+                }
+                OpCode::AssertStack(snapshot, line) => {
+                    let snapshot = chunk.constants[snapshot as usize].as_array().borrow();
+                    if let Err(mismatch) = self.check_stack(current_frame.slots, &snapshot) {
+                        return Err(RuntimeError {
+                            kind: ErrorKind::Assertion,
+                            ..RuntimeError::new(format!(
+                                "stack assertion on line {} failed: {mismatch}",
+                                line + 1
+                            ))
+                        });
+                    }
                 }
-                OpCode::AssertNe => {
+                OpCode::Exit => break Ok(()),
+                OpCode::PushHandler(catch_ip) => self.handlers.push(Handler {
+                    frame_count: self.frame_count,
+                    stack_len: self.stack.len(),
+                    catch_ip,
+                }),
+                OpCode::PopHandler => {
+                    self.handlers.pop();
+                }
+                OpCode::SkipDefault(index, offset) => {
+                    if !matches!(
+                        self.stack[index as usize + 1 + current_frame.slots],
+                        Value::None
+                    ) {
+                        ip = offset;
+                    }
+                }
+                OpCode::Tuple(count) => {
+                    let elements = self.stack.split_off(self.stack.len() - count);
+                    self.stack.push(Value::Tuple(Rc::new(elements)));
+                }
+                OpCode::Array(count) => {
+                    let elements = self.stack.split_off(self.stack.len() - count);
+                    self.stack.push(Value::Array(rcrf(elements)));
+                }
+                OpCode::Unpack(count) | OpCode::UnpackRest(count) => {
+                    let value = pop!();
+                    let elements = match &value {
+                        Value::Tuple(elements) => elements.as_ref().clone(),
+                        Value::Array(elements) => elements.borrow().clone(),
+                        _ => {
+                            return Err(RuntimeError::new(format!(
+                                "only tuples and arrays can be destructured, got {}",
+                                describe_value(&value)
+                            )))
+                        }
+                    };
+                    let rest = matches!(instruction, OpCode::UnpackRest(_));
+                    if elements.len() < count || (!rest && elements.len() != count) {
+                        return Err(RuntimeError::new(format!(
+                            "expected {}{count} element(s) to destructure, {} has {}",
+                            if rest { "at least " } else { "" },
+                            describe_value(&value),
+                            elements.len()
+                        )));
+                    }
+                    let mut elements = elements.into_iter();
+                    self.stack.extend(elements.by_ref().take(count));
+                    if rest {
+                        self.stack.push(Value::Array(rcrf(elements.collect())));
+                    }
+                }
+                OpCode::Is => {
                     let rhs = pop!();
                     let lhs = pop!();
-
-                    assert_ne!(lhs, rhs);
+                    let Value::Tagged(tagged) = &lhs else {
+                        self.stack.push(Value::Boolean(false));
+                        continue;
+                    };
+                    let tagged = tagged.borrow();
+                    let is = match &rhs {
+                        Value::Enum(enumeration) => Rc::ptr_eq(&tagged.enumeration, enumeration),
+                        Value::Variant(enumeration, tag) => {
+                            Rc::ptr_eq(&tagged.enumeration, enumeration) && tagged.tag == *tag
+                        }
+                        Value::Tagged(other) => {
+                            let other = other.borrow();
+                            Rc::ptr_eq(&tagged.enumeration, &other.enumeration)
+                                && tagged.tag == other.tag
+                        }
+                        _ => {
+                            return Err(RuntimeError::new(format!(
+                                "`is` expects an enum or one of its variants, got {}",
+                                describe_value(&rhs)
+                            )))
+                        }
+                    };
+                    self.stack.push(Value::Boolean(is));
+                }
+                OpCode::IsNumber => {
+                    let value = pop!();
+                    self.stack.push(Value::Boolean(matches!(
+                        value,
+                        Value::Int(_) | Value::Number(_)
+                    )));
+                }
+                OpCode::NoMatch => {
+                    let value = pop!();
+                    return Err(RuntimeError::new(format!(
+                        "no match arm fits {}",
+                        describe_value(&value)
+                    )));
+                }
+                OpCode::Throw => {
+                    let value = pop!();
+                    return Err(RuntimeError::thrown(value));
                 }
-                OpCode::Exit => break,
                 OpCode::Return => {
                     let returning = pop!();
                     self.frame_count -= 1;
+                    // returning from inside a `try` leaves it
+                    while let Some(handler) = self.handlers.last() {
+                        if handler.frame_count <= self.frame_count {
+                            break;
+                        }
+                        self.handlers.pop();
+                    }
 
                     if self.frame_count == 0 {
-                        println!("vm took {}", start.elapsed().as_secs_f64());
-                        return;
+                        return Ok(());
                     }
 
                     current_frame = &self.callframes[self.frame_count - 1];
@@ -298,24 +680,76 @@ impl VirtualMachine {
                     self.stack.truncate(self.callframes[self.frame_count].slots);
                     self.stack.push(returning);
                 }
-                OpCode::Call(arg_count) => {
-                    let callee = &self.stack[self.stack.len() - (1 + arg_count)];
-                    let Value::Function(callee) = callee else {
-                        panic!()
+                OpCode::Call(arg_count) | OpCode::CallNamed(arg_count, _) => {
+                    self.callframes[self.frame_count - 1].ip = ip;
+                    let names: Vec<usize> = match instruction {
+                        OpCode::CallNamed(_, names) => chunk.constants[*names as usize]
+                            .as_array()
+                            .borrow()
+                            .iter()
+                            .map(|name| name.as_string().0)
+                            .collect(),
+                        _ => Vec::new(),
                     };
-                    let callee: *const Function = callee.as_ptr() as *const _;
-
-                    self.call(callee, arg_count);
-                    self.callframes[self.frame_count - 2].ip = ip;
+                    let pushed_frame = self.call_value(arg_count, &names)?;
 
                     // prepares for the next callframe
-                    {
-                        current_frame = &self.callframes[self.frame_count - 1];
+                    current_frame = &self.callframes[self.frame_count - 1];
+                    if pushed_frame {
                         function = read_current_frame_fn!();
                         chunk = &function.chunk;
                         ip = 0;
                     }
                 }
+                OpCode::Invoke(name, arg_count) => {
+                    let name = chunk.constants[name as usize].as_string().0;
+                    self.callframes[self.frame_count - 1].ip = ip;
+                    let pushed_frame = self.invoke(name, arg_count)?;
+
+                    current_frame = &self.callframes[self.frame_count - 1];
+                    if pushed_frame {
+                        function = read_current_frame_fn!();
+                        chunk = &function.chunk;
+                        ip = 0;
+                    }
+                }
+                OpCode::Class(name) => {
+                    let name: String = (*chunk.constants[name as usize].as_string()).into();
+                    self.stack.push(Value::Class(rcrf(Class::new(name))));
+                }
+                OpCode::Method(name) => {
+                    let name = chunk.constants[name as usize].as_string().0;
+                    let Value::Function(method) = pop!() else {
+                        unreachable!("methods are always functions")
+                    };
+                    let Value::Class(class) = self.stack.last().unwrap() else {
+                        unreachable!("methods are always added to a class")
+                    };
+                    class.borrow_mut().methods.insert(name, method);
+                }
+                OpCode::GetProperty(name) => {
+                    let name = *chunk.constants[name as usize].as_string();
+                    let object = pop!();
+                    self.stack.push(get_property(&object, name)?);
+                }
+                OpCode::SetProperty(name) => {
+                    let name = chunk.constants[name as usize].as_string();
+                    let value = pop!();
+                    let object = pop!();
+                    let Value::Instance(instance) = &object else {
+                        return Err(RuntimeError::new(format!(
+                            "only instances have fields, tried to set `{}` on {}",
+                            String::from(*name),
+                            describe_value(&object)
+                        )));
+                    };
+                    instance.borrow_mut().fields.insert(name.0, value.clone());
+                    self.stack.push(value);
+                }
+                OpCode::GetSelf => {
+                    let receiver = self.stack[current_frame.slots].clone();
+                    self.stack.push(receiver);
+                }
 
                 OpCode::Less => {
                     binary_op_bool!(<)
@@ -333,3 +767,132 @@ impl VirtualMachine {
         }
     }
 }
+
+fn assertion_failed(
+    info: &AssertionInfo,
+    message: Option<Value>,
+    operands: Option<(&Value, &Value)>,
+) -> RuntimeError {
+    let mut text = format!("assertion failed: {}", info.source);
+    if let Some(message) = message {
+        text.push_str(&format!(", {message}"));
+    }
+    if let Some((lhs, rhs)) = operands {
+        text.push_str(&format!(
+            "\n  left: {}\n right: {}",
+            describe_value(lhs),
+            describe_value(rhs)
+        ));
+    }
+    RuntimeError {
+        kind: ErrorKind::Assertion,
+        ..RuntimeError::at(text, info.line, info.column)
+    }
+}
+
+fn operands_error(op: &str, lhs: &Value, rhs: &Value) -> RuntimeError {
+    RuntimeError::new(format!(
+        "cannot apply `{op}` to {} and {}",
+        describe_value(lhs),
+        describe_value(rhs)
+    ))
+}
+
+/// `object.name`
+fn get_property(object: &Value, name: InternedString) -> Result<Value, RuntimeError> {
+    let missing = || {
+        RuntimeError::new(format!(
+            "{} has no property `{}`",
+            describe_value(object),
+            String::from(name)
+        ))
+    };
+    match object {
+        Value::Instance(instance) => {
+            let instance = instance.borrow();
+            if let Some(field) = instance.fields.get(&name.0) {
+                Ok(field.clone())
+            } else if let Some(method) = instance.class.borrow().methods.get(&name.0) {
+                Ok(Value::BoundMethod(rcrf(BoundMethod {
+                    receiver: object.clone(),
+                    method: method.clone(),
+                })))
+            } else {
+                Err(missing())
+            }
+        }
+        Value::Error(error) => error_property(&error.borrow(), name),
+        Value::Enum(enumeration) => {
+            let tag = enumeration
+                .borrow()
+                .tag(&String::from(name))
+                .ok_or_else(missing)?;
+            // variants without fields don't need to be called
+            if enumeration.borrow().variants[tag].fields.is_empty() {
+                Ok(Value::Tagged(rcrf(Tagged {
+                    enumeration: enumeration.clone(),
+                    tag,
+                    fields: Vec::new(),
+                })))
+            } else {
+                Ok(Value::Variant(enumeration.clone(), tag))
+            }
+        }
+        Value::Tagged(tagged) => tagged
+            .borrow()
+            .field(&String::from(name))
+            .cloned()
+            .ok_or_else(missing),
+        Value::Tuple(elements) => String::from(name)
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| elements.get(index))
+            .cloned()
+            .ok_or_else(missing),
+        _ => Err(RuntimeError::new(format!(
+            "only instances have properties, tried to read `{}` from {}",
+            String::from(name),
+            describe_value(object)
+        ))),
+    }
+}
+
+/// `error.message` and `error.trace` on a caught error
+fn error_property(error: &ErrorValue, name: InternedString) -> Result<Value, RuntimeError> {
+    let name: String = name.into();
+    match name.as_str() {
+        "message" => Ok(error.message.as_str().to_value()),
+        "trace" => Ok(Value::Array(rcrf(
+            error
+                .trace
+                .iter()
+                .map(|frame| frame.as_str().to_value())
+                .collect(),
+        ))),
+        _ => Err(RuntimeError::new(format!(
+            "errors have no property `{name}`"
+        ))),
+    }
+}
+
+/// whether to take a branch, only booleans can decide
+fn condition(value: &Value) -> Result<bool, RuntimeError> {
+    match value {
+        Value::Boolean(bool) => Ok(*bool),
+        value => Err(RuntimeError::new(format!(
+            "a condition has to be a boolean, got {}",
+            describe_value(value)
+        ))),
+    }
+}
+fn arity_error(function: &Function, got: usize) -> RuntimeError {
+    let expected = match (function.required, function.arity, function.variadic) {
+        (required, _, true) => format!("at least {required}"),
+        (required, arity, false) if required == arity => format!("{arity}"),
+        (required, arity, false) => format!("{required} to {arity}"),
+    };
+    RuntimeError::new(format!(
+        "`{}` expects {expected} argument(s), got {got}",
+        function.name
+    ))
+}
@@ -1,3 +1,186 @@
-use super::VirtualMachine;
+use std::cmp::Ordering;
 
-impl VirtualMachine {}
+use crate::common::value::Value;
+
+use super::{error::RuntimeError, operands_error, VirtualMachine};
+
+impl VirtualMachine {
+    /// compares the stack of the frame starting at `slots` against `expected`,
+    /// describing the first difference if there is one
+    pub fn check_stack(&self, slots: usize, expected: &[Value]) -> Result<(), String> {
+        // the first slot is the function being run
+        let actual = &self.stack[slots + 1..];
+        let describe = |values: &[Value]| {
+            let values: Vec<String> = values.iter().map(describe_value).collect();
+            format!("[{}]", values.join(", "))
+        };
+        let mismatch = expected
+            .iter()
+            .zip(actual)
+            .position(|(expected, actual)| expected != actual);
+        let detail = match mismatch {
+            Some(slot) => format!(
+                "slot {slot} is {} instead of {}",
+                describe_value(&actual[slot]),
+                describe_value(&expected[slot])
+            ),
+            None if expected.len() == actual.len() => return Ok(()),
+            None if expected.len() > actual.len() => {
+                format!("{} value(s) missing", expected.len() - actual.len())
+            }
+            None => format!("{} extra value(s)", actual.len() - expected.len()),
+        };
+        Err(format!(
+            "expected {} but the stack is {}, {detail}",
+            describe(expected),
+            describe(actual)
+        ))
+    }
+}
+
+/// like `Display`, but strings are quoted and it doesn't panic on void
+pub fn describe_value(value: &Value) -> String {
+    match value {
+        Value::String(_) => format!("{:?}", value.to_string()),
+        value => value.to_string(),
+    }
+}
+
+/// the operators that work on numbers, ints stay ints unless they meet a float
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arithmetic {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    IntDiv,
+    Rem,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+}
+impl Arithmetic {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Arithmetic::Add => "+",
+            Arithmetic::Sub => "-",
+            Arithmetic::Mul => "*",
+            Arithmetic::Div => "/",
+            Arithmetic::IntDiv => "~/",
+            Arithmetic::Rem => "%",
+            Arithmetic::BitAnd => "&",
+            Arithmetic::BitOr => "|",
+            Arithmetic::BitXor => "^",
+            Arithmetic::Shl => "<<",
+            Arithmetic::Shr => ">>",
+        }
+    }
+    /// bitwise operators only take ints
+    fn is_bitwise(self) -> bool {
+        matches!(
+            self,
+            Arithmetic::BitAnd
+                | Arithmetic::BitOr
+                | Arithmetic::BitXor
+                | Arithmetic::Shl
+                | Arithmetic::Shr
+        )
+    }
+}
+
+/// `lhs op rhs`, mixing an int with a float gives a float
+pub fn arithmetic(op: Arithmetic, lhs: &Value, rhs: &Value) -> Result<Value, RuntimeError> {
+    match (lhs, rhs) {
+        (Value::Int(lhs), Value::Int(rhs)) => int_arithmetic(op, *lhs, *rhs),
+        (Value::Int(_) | Value::Number(_), Value::Int(_) | Value::Number(_))
+            if !op.is_bitwise() =>
+        {
+            Ok(Value::Number(float_arithmetic(
+                op,
+                as_float(lhs),
+                as_float(rhs),
+            )))
+        }
+        _ => Err(operands_error(op.symbol(), lhs, rhs)),
+    }
+}
+
+fn int_arithmetic(op: Arithmetic, lhs: i64, rhs: i64) -> Result<Value, RuntimeError> {
+    let overflow =
+        || RuntimeError::new(format!("integer overflow in `{lhs} {} {rhs}`", op.symbol()));
+    if matches!(op, Arithmetic::IntDiv | Arithmetic::Rem) && rhs == 0 {
+        return Err(RuntimeError::new(format!(
+            "division by zero in `{lhs} {} {rhs}`",
+            op.symbol()
+        )));
+    }
+    if matches!(op, Arithmetic::Shl | Arithmetic::Shr) && !(0..64).contains(&rhs) {
+        return Err(RuntimeError::new(format!(
+            "cannot shift by {rhs}, shifts have to be between 0 and 63"
+        )));
+    }
+    let result = match op {
+        Arithmetic::Add => lhs.checked_add(rhs),
+        Arithmetic::Sub => lhs.checked_sub(rhs),
+        Arithmetic::Mul => lhs.checked_mul(rhs),
+        // `/` always divides exactly, `~/` is the one that stays an int
+        Arithmetic::Div => return Ok(Value::Number(lhs as f64 / rhs as f64)),
+        // both round towards negative infinity, so `-7 ~/ 2` is -4 and
+        // `-7 % 2` is 1
+        Arithmetic::IntDiv => lhs.checked_div(rhs).map(|quotient| {
+            if lhs % rhs != 0 && (lhs < 0) != (rhs < 0) {
+                quotient - 1
+            } else {
+                quotient
+            }
+        }),
+        Arithmetic::Rem => lhs.checked_rem(rhs).map(|remainder| {
+            if remainder != 0 && (remainder < 0) != (rhs < 0) {
+                remainder + rhs
+            } else {
+                remainder
+            }
+        }),
+        Arithmetic::BitAnd => Some(lhs & rhs),
+        Arithmetic::BitOr => Some(lhs | rhs),
+        Arithmetic::BitXor => Some(lhs ^ rhs),
+        // shifting bits out of the top counts as overflowing
+        Arithmetic::Shl => Some(lhs << rhs).filter(|shifted| shifted >> rhs == lhs),
+        Arithmetic::Shr => Some(lhs >> rhs),
+    };
+    result.map(Value::Int).ok_or_else(overflow)
+}
+
+fn float_arithmetic(op: Arithmetic, lhs: f64, rhs: f64) -> f64 {
+    match op {
+        Arithmetic::Add => lhs + rhs,
+        Arithmetic::Sub => lhs - rhs,
+        Arithmetic::Mul => lhs * rhs,
+        Arithmetic::Div => lhs / rhs,
+        Arithmetic::IntDiv => (lhs / rhs).floor(),
+        Arithmetic::Rem => lhs - rhs * (lhs / rhs).floor(),
+        _ => unreachable!("{op:?} only takes ints"),
+    }
+}
+
+fn as_float(value: &Value) -> f64 {
+    match value {
+        Value::Int(int) => *int as f64,
+        Value::Number(number) => *number,
+        _ => unreachable!(),
+    }
+}
+
+/// orders two numbers, `None` if either isn't one and `Some(None)` when one is
+/// NaN
+pub fn compare_numbers(lhs: &Value, rhs: &Value) -> Option<Option<Ordering>> {
+    match (lhs, rhs) {
+        (Value::Int(lhs), Value::Int(rhs)) => Some(lhs.partial_cmp(rhs)),
+        (Value::Int(_) | Value::Number(_), Value::Int(_) | Value::Number(_)) => {
+            Some(as_float(lhs).partial_cmp(&as_float(rhs)))
+        }
+        _ => None,
+    }
+}
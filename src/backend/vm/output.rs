@@ -0,0 +1,28 @@
+use std::{cell::RefCell, fmt::Display, rc::Rc};
+
+/// where `print` statements end up
+#[derive(Debug, Clone, Default)]
+pub enum Output {
+    #[default]
+    Stdout,
+    /// collects every printed line, used by the script tests to compare against
+    /// the expected output
+    Captured(Rc<RefCell<String>>),
+}
+
+impl Output {
+    pub fn captured() -> (Output, Rc<RefCell<String>>) {
+        let buffer = Rc::new(RefCell::new(String::new()));
+        (Output::Captured(buffer.clone()), buffer)
+    }
+    pub fn print_line(&self, value: impl Display) {
+        match self {
+            Output::Stdout => println!("{value}"),
+            Output::Captured(buffer) => {
+                let mut buffer = buffer.borrow_mut();
+                buffer.push_str(&value.to_string());
+                buffer.push('\n');
+            }
+        }
+    }
+}
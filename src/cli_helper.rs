@@ -2,40 +2,120 @@ use std::path::Path;
 
 use colored::Colorize;
 
-use crate::frontend::scanner::Position;
+use crate::frontend::span::{LineIndex, Position, Span};
 
 #[derive(Debug, Clone)]
 pub struct Diagnostics<'a> {
     pub file_path: &'a Path,
+    /// every message that went through [`Diagnostics::log`], without colors,
+    /// so callers (like the script tests) can inspect what was reported
+    pub logged: Vec<String>,
+    /// like `logged`, but for [`Diagnostics::warn`]
+    pub warnings: Vec<String>,
+    /// turns the spans of whatever is reported into lines and columns
+    pub line_index: LineIndex,
+    /// everything logged or warned about with the span it points at, for
+    /// tools that place messages themselves, like the language server
+    pub reports: Vec<Report>,
+    /// only collect what's reported, stdout may be a protocol and not a
+    /// terminal
+    pub quiet: bool,
+}
+/// one message from [`Diagnostics::log`] or [`Diagnostics::warn`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub span: Option<Span>,
+    pub message: String,
+    pub warning: bool,
 }
 impl Default for Diagnostics<'_> {
     fn default() -> Self {
         Self {
             file_path: Path::new(""),
+            logged: Vec::new(),
+            warnings: Vec::new(),
+            line_index: LineIndex::default(),
+            reports: Vec::new(),
+            quiet: false,
         }
     }
 }
 impl<'a> Diagnostics<'a> {
     pub fn new(path: &'a Path) -> Self {
-        Diagnostics { file_path: path }
+        Diagnostics {
+            file_path: path,
+            logged: Vec::new(),
+            warnings: Vec::new(),
+            line_index: LineIndex::default(),
+            reports: Vec::new(),
+            quiet: false,
+        }
     }
 
     pub fn file_path(&self) -> &str {
         self.file_path.to_str().unwrap()
     }
-    pub fn log(&mut self, position: Option<&Position>, title: &str, msg: String) {
+    /// the source that spans passed to [`Diagnostics::log`] and
+    /// [`Diagnostics::warn`] point into
+    pub fn set_source(&mut self, source: &str) {
+        self.line_index = LineIndex::new(source);
+    }
+    pub fn position(&self, span: Span) -> Position {
+        self.line_index.position(span.start)
+    }
+    pub fn line(&self, span: Span) -> usize {
+        self.position(span).line
+    }
+    pub fn log(&mut self, span: Option<Span>, title: &str, msg: String) {
         let mut location: String = String::new();
-        if let Some(position) = position {
-            location = format!("{}:{}", position.line + 1, position.start_in_line + 1);
+        if let Some(span) = span {
+            let position = self.position(span);
+            location = format!("{}:{}", position.line + 1, position.column + 1);
+        }
+        if !self.quiet {
+            println!(
+                "[ {} ] - {title} \n\t{} - {}",
+                "rottenmangos".bold(),
+                format!("{}:{location}", self.file_path()).bold().yellow(),
+                msg
+            );
         }
-        print!(
-            "[ {} ] - {title} \n\t{} - {}",
-            "rottenmangos".bold(),
-            format!("{}:{location}", self.file_path()).bold().yellow(),
-            msg
-        )
+        self.logged
+            .push(format!("{}:{location} - {msg}", self.file_path()));
+        self.reports.push(Report {
+            span,
+            message: msg,
+            warning: false,
+        });
+    }
+    /// something that compiles but probably isn't what was meant
+    pub fn warn(&mut self, span: Span, msg: &str) {
+        let position = self.position(span);
+        let location = format!(
+            "{}:{}:{}",
+            self.file_path(),
+            position.line + 1,
+            position.column + 1
+        );
+        if !self.quiet {
+            println!(
+                "[ {} ] - Warning \n\t{} - {}",
+                "rottenmangos".bold(),
+                location.bold().yellow(),
+                msg
+            );
+        }
+        self.warnings.push(format!("{location} - {msg}"));
+        self.reports.push(Report {
+            span: Some(span),
+            message: msg.to_owned(),
+            warning: true,
+        });
     }
     pub fn log_wall(&mut self, title: &str, msg: &[String]) {
+        if self.quiet {
+            return;
+        }
         println!(
             "[ {} ] - {title}\n\t{}",
             "rottenmangos".bold().black(),
@@ -3,6 +3,21 @@ use super::{opcode::OpCode, value::Value};
 pub struct Chunk {
     pub code: Vec<OpCode>,
     pub constants: Vec<Value>,
+    /// what the assertion opcodes need to explain a failure, they index into this
+    pub assertions: Vec<AssertionInfo>,
+    /// the line and column (both starting at 0) each instruction was compiled
+    /// from, runtime errors point there
+    pub locations: Vec<Option<(usize, usize)>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssertionInfo {
+    /// the asserted expression as written in the source
+    pub source: String,
+    pub line: usize,
+    pub column: usize,
+    /// whether a message was pushed after the operands
+    pub has_message: bool,
 }
 
 impl Chunk {
@@ -10,19 +25,34 @@ impl Chunk {
         Chunk {
             code: vec![],
             constants: vec![],
+            assertions: vec![],
+            locations: vec![],
         }
     }
     pub fn emit_op(&mut self, op: OpCode) {
-        self.code.push(op)
+        self.code.push(op);
+        self.locations.push(None);
     }
     pub fn emit_many(&mut self, mut ops: Vec<OpCode>) {
+        self.locations.resize(self.code.len() + ops.len(), None);
         self.code.append(&mut ops);
     }
+    /// gives the instructions from `start` on the location, unless a node
+    /// inside the one that emitted them already did
+    pub fn locate(&mut self, start: usize, location: (usize, usize)) {
+        for instruction in &mut self.locations[start..] {
+            instruction.get_or_insert(location);
+        }
+    }
     pub fn emit_constant(&mut self, value: Value) -> u16 {
         let pos = self.emit_value(value);
         self.emit_op(OpCode::Constant(pos));
         pos
     }
+    pub fn add_assertion(&mut self, info: AssertionInfo) -> u16 {
+        self.assertions.push(info);
+        (self.assertions.len() - 1) as u16
+    }
     pub fn emit_value(&mut self, value: Value) -> u16 {
         let pos = self.constants.len();
         self.constants.push(value);
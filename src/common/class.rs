@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+use super::{
+    function::Function,
+    value::{Ptr, Value},
+};
+
+/// the runtime side of a `class` declaration, created by `OpCode::Class`
+#[derive(Debug, Clone, Default)]
+pub struct Class {
+    pub name: String,
+    /// keyed by the interned name of the method
+    pub methods: HashMap<usize, Ptr<Function>>,
+}
+
+impl Class {
+    pub fn new(name: String) -> Class {
+        Class {
+            name,
+            methods: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Instance {
+    pub class: Ptr<Class>,
+    /// keyed by the interned name of the field
+    pub fields: HashMap<usize, Value>,
+}
+
+impl Instance {
+    pub fn new(class: Ptr<Class>) -> Instance {
+        Instance {
+            class,
+            fields: HashMap::new(),
+        }
+    }
+}
+
+/// a method that was read off an instance, `self` is already filled in
+#[derive(Debug, Clone)]
+pub struct BoundMethod {
+    pub receiver: Value,
+    pub method: Ptr<Function>,
+}
@@ -41,7 +41,14 @@ pub fn diassasemble_instruction(
         | OpCode::Constant(pos)
         | OpCode::SetGlobal(pos)
         | OpCode::GetGlobal(pos)
-        | OpCode::DefineLocal(pos) => {
+        | OpCode::DefineLocal(pos)
+        | OpCode::AssertStack(pos, _)
+        | OpCode::Class(pos)
+        | OpCode::Method(pos)
+        | OpCode::GetProperty(pos)
+        | OpCode::SetProperty(pos)
+        | OpCode::Invoke(pos, _)
+        | OpCode::CallNamed(_, pos) => {
             let constant = &chunk.constants[*pos as usize];
 
             println!("{} <{:?}>", instruction, constant)
@@ -50,9 +57,17 @@ pub fn diassasemble_instruction(
         OpCode::JumpTo(offset)
         | OpCode::JumpToIfFalse(offset)
         | OpCode::PopJumpToIfFalse(offset)
-        | OpCode::Call(offset) => {
+        | OpCode::PushHandler(offset)
+        | OpCode::Call(offset)
+        | OpCode::Tuple(offset)
+        | OpCode::Array(offset)
+        | OpCode::Unpack(offset)
+        | OpCode::UnpackRest(offset) => {
             println!("{} {}", instruction, offset)
         }
+        OpCode::SkipDefault(pos, offset) => {
+            println!("{} {} {}", instruction, pos, offset)
+        }
         OpCode::GetLocal(pos) | OpCode::SetLocal(pos) => {
             println!("{} {}", instruction, pos)
         }
@@ -0,0 +1,54 @@
+use super::value::{Ptr, Value};
+
+/// the runtime side of an `enum` declaration
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enum {
+    pub name: String,
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub name: String,
+    /// names of the positional fields, empty for `Empty` in `enum Shape { Empty }`
+    pub fields: Vec<String>,
+}
+
+impl Enum {
+    /// the tag of the variant called `name`
+    pub fn tag(&self, name: &str) -> Option<usize> {
+        self.variants
+            .iter()
+            .position(|variant| variant.name == name)
+    }
+}
+
+/// a value created by one of an enum's variants, like `Shape.Circle(2)`
+#[derive(Debug, Clone)]
+pub struct Tagged {
+    pub enumeration: Ptr<Enum>,
+    /// index into the enum's variants
+    pub tag: usize,
+    pub fields: Vec<Value>,
+}
+
+impl Tagged {
+    pub fn variant_name(&self) -> String {
+        let enumeration = self.enumeration.borrow();
+        format!(
+            "{}.{}",
+            enumeration.name, enumeration.variants[self.tag].name
+        )
+    }
+    /// a field by its declared name or its position
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        let index = match name.parse::<usize>() {
+            Ok(index) => index,
+            Err(_) => self.enumeration.borrow().variants[self.tag]
+                .fields
+                .iter()
+                .position(|field| field == name)?,
+        };
+        self.fields.get(index)
+    }
+}
@@ -0,0 +1,16 @@
+use std::fmt::{self, Display};
+
+/// what `catch` binds when the vm itself raised the error, like calling a
+/// number, thrown values are handed over as they are
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorValue {
+    pub message: String,
+    /// names of the functions that were running, innermost first
+    pub trace: Vec<String>,
+}
+
+impl Display for ErrorValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<error: {}>", self.message)
+    }
+}
@@ -2,15 +2,29 @@ use super::chunk::Chunk;
 #[derive(Debug, Clone, Default)]
 pub struct Function {
     pub chunk: Chunk,
+    /// parameters that aren't variadic, including the ones with defaults
     pub arity: u8,
+    /// parameters without a default, they always come first
+    pub required: u8,
+    /// whether a last `...rest` parameter collects the extra arguments
+    pub variadic: bool,
+    /// names of every parameter, for named arguments
+    pub parameters: Vec<String>,
     pub name: String,
 }
 
 impl Function {
+    /// the locals the arguments end up in, the rest array counts as one
+    pub fn slot_count(&self) -> usize {
+        self.arity as usize + self.variadic as usize
+    }
     pub fn new() -> Function {
         Function {
             chunk: Chunk::new(),
             arity: 0,
+            required: 0,
+            variadic: false,
+            parameters: Vec::new(),
             name: String::from("main"),
         }
     }
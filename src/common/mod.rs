@@ -1,5 +1,8 @@
 pub mod chunk;
+pub mod class;
 pub mod debug;
+pub mod enums;
+pub mod error;
 pub mod function;
 pub mod interner;
 pub mod natives;
@@ -17,7 +17,7 @@ pub enum OpCode {
     GetLocal(u16),
     DefineLocal(ConstantLocation),
     SetLocal(u16),
-    /// this doesn't clone off the stack, and uses mem::take instead of cloning
+    /// pops the value off the stack instead of cloning it
     SetLocalConsumes(u16),
     DefineGlobal(ConstantLocation),
     GetGlobal(ConstantLocation),
@@ -27,12 +27,23 @@ pub enum OpCode {
     JumpToIfFalse(Offset),
     JumpTo(Offset),
     Call(usize),
+    /// like `Call`, the constant is an array with the names of the last
+    /// arguments
+    CallNamed(usize, ConstantLocation),
+    /// jumps over the code computing a parameter's default when the argument
+    /// (in local `.0`) was given
+    SkipDefault(u16, Offset),
     Greater,
     Less,
     GreaterEq,
     LessEq,
-    AssertEq,
-    AssertNe,
+    /// the field indexes into the chunk's assertions
+    AssertEq(u16),
+    AssertNe(u16),
+    Assert(u16),
+    /// compares the current frame's stack to the array constant, the second field
+    /// is the line of the `#assert_stack` for the error message
+    AssertStack(ConstantLocation, usize),
     True,
     False,
     Void,
@@ -44,8 +55,50 @@ pub enum OpCode {
     Sub,
     Div,
     Mul,
+    /// `~/`
+    IntDiv,
+    Rem,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
     Return,
     Nop,
+    /// pushes a new class with no methods, the constant is its name
+    Class(ConstantLocation),
+    /// pops a function and adds it as a method to the class below it
+    Method(ConstantLocation),
+    GetProperty(ConstantLocation),
+    SetProperty(ConstantLocation),
+    /// `receiver.name(args)`, the constant is the name, the second field the
+    /// argument count
+    Invoke(ConstantLocation, usize),
+    /// pushes the receiver of the current method
+    GetSelf,
+    /// starts a `try` block, errors raised until the matching `PopHandler` jump
+    /// to the offset with the error on top of the stack
+    PushHandler(Offset),
+    PopHandler,
+    /// pops a value and raises it as an error
+    Throw,
+    /// builds a tuple out of that many values
+    Tuple(usize),
+    /// builds an array out of that many values
+    Array(usize),
+    /// replaces a tuple or array with its elements, it has to have exactly
+    /// that many
+    Unpack(usize),
+    /// like `Unpack`, but any elements after the first that many are pushed
+    /// as one array
+    UnpackRest(usize),
+    /// `value is Enum.Variant`
+    Is,
+    /// replaces the value on top of the stack with whether it's a number, so
+    /// range patterns can fail instead of raising on other values
+    IsNumber,
+    /// raised when no arm of a `match` fits the value on top of the stack
+    NoMatch,
     CallNative(u16),
     CallNativeArgPtr(u16, *const [Value]),
 }
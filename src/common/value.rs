@@ -8,6 +8,9 @@ use std::{
 };
 
 use super::{
+    class::{BoundMethod, Class, Instance},
+    enums::{Enum, Tagged},
+    error::ErrorValue,
     function::Function,
     interner::{InternedString, STRING_INTERNER},
 };
@@ -16,10 +19,20 @@ use super::{
 #[derive(Clone, Default)]
 pub enum Value {
     Number(f64),
+    Int(i64),
     Boolean(bool),
     String(InternedString),
     Function(Ptr<Function>),
     Array(Ptr<Vec<Value>>),
+    Tuple(Rc<Vec<Value>>),
+    Class(Ptr<Class>),
+    Instance(Ptr<Instance>),
+    BoundMethod(Ptr<BoundMethod>),
+    Error(Ptr<ErrorValue>),
+    Enum(Ptr<Enum>),
+    /// `Shape.Circle` before it's called, the field is the tag
+    Variant(Ptr<Enum>, usize),
+    Tagged(Ptr<Tagged>),
     Void,
     #[default]
     None,
@@ -28,7 +41,9 @@ impl Debug for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Array(arg0) => f.debug_tuple("Array").field(arg0).finish(),
+            Self::Tuple(arg0) => f.debug_tuple("Tuple").field(arg0).finish(),
             Self::Number(arg0) => f.debug_tuple("Number").field(arg0).finish(),
+            Self::Int(arg0) => f.debug_tuple("Int").field(arg0).finish(),
             Self::Boolean(arg0) => f.debug_tuple("Boolean").field(arg0).finish(),
             Self::String(arg0) => {
                 let mut tuple = f.debug_tuple("String");
@@ -38,6 +53,31 @@ impl Debug for Value {
                 tuple.finish()
             }
             Self::Function(_arg0) => f.debug_tuple("Function").finish(),
+            Self::Class(class) => f.debug_tuple("Class").field(&class.borrow().name).finish(),
+            Self::Instance(instance) => f
+                .debug_tuple("Instance")
+                .field(&instance.borrow().class.borrow().name)
+                .finish(),
+            Self::BoundMethod(bound) => f
+                .debug_tuple("BoundMethod")
+                .field(&bound.borrow().method.borrow().name)
+                .finish(),
+            Self::Error(error) => f.debug_tuple("Error").field(&error.borrow()).finish(),
+            Self::Enum(enumeration) => f
+                .debug_tuple("Enum")
+                .field(&enumeration.borrow().name)
+                .finish(),
+            Self::Variant(enumeration, tag) => f
+                .debug_tuple("Variant")
+                .field(&enumeration.borrow().variants[*tag].name)
+                .finish(),
+            Self::Tagged(tagged) => {
+                let tagged = tagged.borrow();
+                f.debug_tuple("Tagged")
+                    .field(&tagged.variant_name())
+                    .field(&tagged.fields)
+                    .finish()
+            }
             Self::Void => write!(f, "Void"),
             Self::None => write!(f, "None"),
         }
@@ -48,8 +88,25 @@ impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::Number(l0), Self::Number(r0)) => l0 == r0,
+            (Self::Int(l0), Self::Int(r0)) => l0 == r0,
+            (Self::Int(int), Self::Number(number)) | (Self::Number(number), Self::Int(int)) => {
+                *int as f64 == *number
+            }
             (Self::Boolean(l0), Self::Boolean(r0)) => l0 == r0,
             (Self::String(l0), Self::String(r0)) => l0.eq(r0),
+            (Self::Class(l0), Self::Class(r0)) => Rc::ptr_eq(l0, r0),
+            (Self::Instance(l0), Self::Instance(r0)) => Rc::ptr_eq(l0, r0),
+            (Self::Error(l0), Self::Error(r0)) => Rc::ptr_eq(l0, r0),
+            (Self::Tuple(l0), Self::Tuple(r0)) => l0 == r0,
+            (Self::Array(l0), Self::Array(r0)) => l0 == r0,
+            (Self::Enum(l0), Self::Enum(r0)) => Rc::ptr_eq(l0, r0),
+            (Self::Variant(l0, l1), Self::Variant(r0, r1)) => Rc::ptr_eq(l0, r0) && l1 == r1,
+            (Self::Tagged(l0), Self::Tagged(r0)) => {
+                let (l0, r0) = (l0.borrow(), r0.borrow());
+                Rc::ptr_eq(&l0.enumeration, &r0.enumeration)
+                    && l0.tag == r0.tag
+                    && l0.fields == r0.fields
+            }
             _ => false,
         }
     }
@@ -63,11 +120,11 @@ impl AsValue for bool {
     }
 }
 impl Value {
-    pub fn as_bool(&self) -> &bool {
-        let Value::Boolean(bool) = &self else {
-            panic!()
+    pub fn as_array(&self) -> &Ptr<Vec<Value>> {
+        let Value::Array(array) = self else {
+            panic!("expected an array, got {:?}", self)
         };
-        bool
+        array
     }
     pub fn as_string(&self) -> &InternedString {
         if let Value::String(string) = self {
@@ -88,6 +145,11 @@ impl AsValue for f64 {
         Value::Number(self)
     }
 }
+impl AsValue for i64 {
+    fn to_value(self) -> Value {
+        Value::Int(self)
+    }
+}
 pub type Ptr<T> = Rc<RefCell<T>>;
 
 pub fn rcrf<T>(inner: T) -> Ptr<T> {
@@ -97,7 +159,9 @@ pub fn rcrf<T>(inner: T) -> Ptr<T> {
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Value::Number(number) => write!(f, "{}", number),
+            // `2.0` rather than `2` so floats can't be mistaken for ints
+            Value::Number(number) => write!(f, "{:?}", number),
+            Value::Int(int) => write!(f, "{}", int),
             Value::String(string) => {
                 let tmp: String = (*string).into();
                 write!(f, "{}", tmp)
@@ -105,16 +169,67 @@ impl Display for Value {
             Value::Boolean(bool) => {
                 write!(f, "{}", bool)
             }
-            Value::None | Value::Void => {
-                panic!("cannot print a void or none");
-            }
+            // only reachable inside another value, `print` refuses them
+            Value::Void => write!(f, "void"),
+            Value::None => write!(f, "none"),
             Value::Function(function) => {
                 write!(f, "<func {:?}>", addr_of!(function))
             }
             Value::Array(array) => {
-                let tmp = array.as_ref().borrow();
-                write!(f, "{:?}", tmp)
+                write!(f, "[")?;
+                write_elements(f, &array.borrow())?;
+                write!(f, "]")
+            }
+            Value::Tuple(elements) => {
+                write!(f, "(")?;
+                write_elements(f, elements)?;
+                // `(1,)` so it doesn't look like a grouping
+                if elements.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+            Value::Class(class) => write!(f, "<class {}>", class.borrow().name),
+            Value::Instance(instance) => {
+                write!(f, "<{} instance>", instance.borrow().class.borrow().name)
             }
+            Value::BoundMethod(bound) => {
+                write!(f, "<method {}>", bound.borrow().method.borrow().name)
+            }
+            Value::Error(error) => write!(f, "{}", error.borrow()),
+            Value::Enum(enumeration) => write!(f, "<enum {}>", enumeration.borrow().name),
+            Value::Variant(enumeration, tag) => {
+                let enumeration = enumeration.borrow();
+                write!(
+                    f,
+                    "<variant {}.{}>",
+                    enumeration.name, enumeration.variants[*tag].name
+                )
+            }
+            Value::Tagged(tagged) => {
+                let tagged = tagged.borrow();
+                write!(f, "{}", tagged.variant_name())?;
+                if !tagged.fields.is_empty() {
+                    write!(f, "(")?;
+                    write_elements(f, &tagged.fields)?;
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// comma separated, strings are quoted so `["a, b"]` and `["a", "b"]` differ
+fn write_elements(f: &mut std::fmt::Formatter<'_>, elements: &[Value]) -> std::fmt::Result {
+    for (i, element) in elements.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        match element {
+            Value::String(_) => write!(f, "{:?}", element.to_string())?,
+            element => write!(f, "{element}")?,
         }
     }
+    Ok(())
 }
@@ -0,0 +1,576 @@
+//! `limesherbet fmt`, prints a file back out in one canonical style, four
+//! spaces of indentation, spaces around operators and one statement per line
+//!
+//! comments aren't part of the ast, they're kept as trivia from the scanner
+//! and put back between the nodes they were written between, a comment in
+//! the middle of an expression moves to the end of its line
+use std::{cell::RefCell, path::Path, rc::Rc};
+
+use crate::{
+    cli_helper::Diagnostics,
+    frontend::{
+        ast::{
+            declaration::{
+                class::ClassDeclaration,
+                enum_declaration::EnumDeclaration,
+                function::{FunctionDeclaration, Parameter},
+                variable_declaration::Binding,
+                Declaration,
+            },
+            directive::Directive,
+            expression::{
+                block::Block,
+                comparison::ComparisonKind,
+                function_expr::FunctionExpr,
+                match_expr::{MatchExpr, Pattern},
+                Expression,
+            },
+            identifier::Identifier,
+            node::{AstPosition, Node},
+            statement::{assertion::AssertionKind, Statement},
+            BinaryOperation,
+        },
+        compiler::{directives::Directives, FunctionType},
+        parser::Parser,
+        scanner::{Scanner, TokenKind},
+        span::Span,
+    },
+};
+
+/// the formatted source, or what was logged if the file doesn't parse
+pub fn format_source(path: &Path, source: &str) -> Result<String, String> {
+    let diagnostics = Rc::new(RefCell::new(Diagnostics::new(path)));
+    let mut parser = Parser::new(
+        Scanner::new(source.to_owned()),
+        diagnostics.clone(),
+        FunctionType::Script,
+        Directives::builtin(),
+    );
+    let nodes = parser.parse_nodes();
+    if parser.had_error {
+        return Err(diagnostics.borrow().logged.join("\n"));
+    }
+
+    // the parser skips over comments, scan again to collect them
+    let mut scanner = Scanner::new(source.to_owned());
+    let doc_comments: Vec<Span> = scanner
+        .scan_thru()
+        .into_iter()
+        .filter(|token| token.kind == TokenKind::DocComment)
+        .map(|token| token.span)
+        .collect();
+    let mut comments = scanner.comments;
+    comments.extend(doc_comments);
+    comments.sort_by_key(|span| span.start);
+
+    let mut formatter = Formatter {
+        source,
+        comments,
+        next_comment: 0,
+        out: String::new(),
+        indent: 0,
+        last_end: 0,
+        at_line_start: true,
+        line_comment: false,
+    };
+    formatter.items(&nodes, source.len(), Formatter::node);
+    if !formatter.out.is_empty() {
+        formatter.out.push('\n');
+    }
+    Ok(formatter.out)
+}
+
+struct Formatter<'s> {
+    source: &'s str,
+    /// every comment in the file, sorted by where they start
+    comments: Vec<Span>,
+    /// the first comment that hasn't been written yet
+    next_comment: usize,
+    out: String,
+    indent: usize,
+    /// where the last thing written ended in the original source
+    last_end: usize,
+    at_line_start: bool,
+    /// whether the current line ends in a `//` comment, nothing can follow it
+    line_comment: bool,
+}
+impl Formatter<'_> {
+    fn write(&mut self, text: &str) {
+        if self.at_line_start {
+            self.out.push_str(&"    ".repeat(self.indent));
+            self.at_line_start = false;
+        }
+        self.out.push_str(text);
+    }
+    fn newline(&mut self) {
+        self.out.push('\n');
+        self.at_line_start = true;
+        self.line_comment = false;
+    }
+    fn start_line(&mut self) {
+        if !self.at_line_start {
+            self.newline();
+        }
+    }
+    fn text(&self, span: Span) -> &str {
+        &self.source[span.range()]
+    }
+    /// whether the original source had an empty line between `last_end` and
+    /// `start`
+    fn blank_line_before(&self, start: usize) -> bool {
+        self.source
+            .get(self.last_end..start)
+            .is_some_and(|between| between.matches('\n').count() > 1)
+    }
+    /// writes the comments that start before `offset`, one that was on the
+    /// same line as what was written last stays at the end of that line
+    fn comments_before(&mut self, offset: usize, first: &mut bool) {
+        while let Some(&span) = self.comments.get(self.next_comment) {
+            if span.start as usize >= offset {
+                break;
+            }
+            self.next_comment += 1;
+            let start = span.start as usize;
+            let same_line =
+                start < self.last_end || !self.source[self.last_end..start].contains('\n');
+            let text = self.text(span).trim_end().to_owned();
+            if same_line && !self.at_line_start && !self.line_comment {
+                self.write(" ");
+            } else {
+                self.start_line();
+                if !*first && self.blank_line_before(start) {
+                    self.newline();
+                }
+                *first = false;
+            }
+            self.write(&text);
+            self.line_comment = text.starts_with("//");
+            self.last_end = self.last_end.max(span.end());
+        }
+    }
+    /// things that go on their own lines, like statements or match arms,
+    /// with the comments that come before `end` between them
+    fn items<T: AstPosition>(
+        &mut self,
+        items: &[T],
+        end: usize,
+        mut write: impl FnMut(&mut Self, &T),
+    ) {
+        let mut first = true;
+        for item in items {
+            let span = item.span();
+            self.comments_before(span.start as usize, &mut first);
+            self.start_line();
+            if !first && self.blank_line_before(span.start as usize) {
+                self.newline();
+            }
+            first = false;
+            write(self, item);
+            self.last_end = self.last_end.max(span.end());
+        }
+        self.comments_before(end, &mut first);
+    }
+    fn comments_within(&self, end: usize) -> bool {
+        self.comments
+            .get(self.next_comment)
+            .is_some_and(|span| (span.start as usize) < end)
+    }
+    /// `{`, the items on their own lines one level deeper, then `}`
+    fn braced<T: AstPosition>(
+        &mut self,
+        items: &[T],
+        open: usize,
+        end: usize,
+        write: impl FnMut(&mut Self, &T),
+    ) {
+        if items.is_empty() && !self.comments_within(end) {
+            self.write("{}");
+            return;
+        }
+        self.write("{");
+        self.last_end = self.last_end.max(open);
+        self.indent += 1;
+        self.items(items, end, write);
+        self.indent -= 1;
+        self.start_line();
+        self.write("}");
+    }
+    fn block(&mut self, block: &Block) {
+        let open = block.span.start as usize + 1;
+        let close = block.span.end() - 1;
+        self.braced(&block.declarations, open, close, Formatter::node);
+        self.last_end = self.last_end.max(block.span.end());
+    }
+    fn node(&mut self, node: &Node) {
+        match node {
+            Node::Expression(expr) => self.expression(expr),
+            Node::Statement(statement) => self.statement(statement),
+            Node::Declaration(declaration) => self.declaration(declaration),
+            Node::Identifier(identifier) => self.write(&identifier.lexeme),
+            Node::Directive(directive) => self.directive(directive),
+            Node::None | Node::Empty => {}
+        }
+    }
+    fn directive(&mut self, directive: &Directive) {
+        self.write("#");
+        self.write(directive.name());
+        // `#name` takes no arguments, `#name []` is an empty list of them
+        if !directive.arguments.is_empty() || self.text(directive.span).ends_with(']') {
+            self.write(" [");
+            self.list(&directive.arguments);
+            self.write("]");
+        }
+    }
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Expression(expr) => {
+                self.expression(expr);
+                // like `if`, a `match` statement doesn't need a `;`
+                if !matches!(expr, Expression::Match(_)) {
+                    self.write(";");
+                }
+            }
+            Statement::Print(value, _) => {
+                self.write("print ");
+                self.node(value);
+                self.write(";");
+            }
+            Statement::Assert(assertion) => {
+                self.write(match assertion.kind {
+                    AssertionKind::Eq => "assert_eq ",
+                    AssertionKind::Ne => "assert_ne ",
+                    AssertionKind::True => "assert ",
+                });
+                self.expression(&assertion.lhs);
+                for expr in assertion.rhs.iter().chain(&assertion.message) {
+                    self.write(", ");
+                    self.expression(expr);
+                }
+                self.write(";");
+            }
+            Statement::Return(return_stmt) => match &return_stmt.expr {
+                Some(expr) => {
+                    self.write("return ");
+                    self.expression(expr);
+                    self.write(";");
+                }
+                None => self.write("return;"),
+            },
+            Statement::Try(try_catch) => {
+                self.write("try ");
+                self.block(&try_catch.body);
+                self.write(" catch (");
+                self.write(&try_catch.binding.lexeme);
+                self.write(") ");
+                self.block(&try_catch.handler);
+            }
+            Statement::Throw(expr, _) => {
+                self.write("throw ");
+                self.expression(expr);
+                self.write(";");
+            }
+        }
+    }
+    fn declaration(&mut self, declaration: &Declaration) {
+        match declaration {
+            Declaration::VariableDeclaration(declaration) => {
+                self.write("let ");
+                self.binding(&declaration.binding);
+                self.write(" = ");
+                self.expression(&declaration.intializer);
+                self.write(";");
+            }
+            Declaration::FunctionDeclaration(declaration) => self.function_declaration(declaration),
+            Declaration::TestDeclaration(declaration) => {
+                self.write("test \"");
+                self.write(&declaration.name);
+                self.write("\" ");
+                self.block(&declaration.block);
+            }
+            Declaration::ClassDeclaration(declaration) => self.class_declaration(declaration),
+            Declaration::EnumDeclaration(declaration) => self.enum_declaration(declaration),
+        }
+    }
+    fn binding(&mut self, binding: &Binding) {
+        match binding {
+            Binding::Name(name) => self.write(&name.lexeme),
+            Binding::Tuple(names) => {
+                self.write("(");
+                self.write(&join_names(names.iter().map(|name| name.lexeme.as_str())));
+                self.write(")");
+            }
+            Binding::Array { names, rest } => {
+                self.write("[");
+                let rest = rest.as_ref().map(|rest| format!("{}...", rest.lexeme));
+                self.write(&join_names(
+                    names
+                        .iter()
+                        .map(|name| name.lexeme.as_str())
+                        .chain(rest.as_deref()),
+                ));
+                self.write("]");
+            }
+        }
+    }
+    fn function_declaration(&mut self, declaration: &FunctionDeclaration) {
+        self.write("func ");
+        self.write(&declaration.name.lexeme);
+        self.parameters(&declaration.parameters);
+        self.write(" ");
+        self.block(&declaration.block);
+    }
+    fn parameters(&mut self, parameters: &[Parameter]) {
+        self.write("(");
+        for (i, parameter) in parameters.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            if parameter.variadic {
+                self.write("...");
+            }
+            self.write(&parameter.name.lexeme);
+            if let Some(default) = &parameter.default {
+                self.write(" = ");
+                self.expression(default);
+            }
+        }
+        self.write(")");
+    }
+    fn class_declaration(&mut self, declaration: &ClassDeclaration) {
+        self.write("class ");
+        self.write(&declaration.name.lexeme);
+        self.write(" ");
+        let open = declaration.name.span().end();
+        let close = declaration.span.end() - 1;
+        self.braced(
+            &declaration.methods,
+            open,
+            close,
+            Formatter::function_declaration,
+        );
+    }
+    fn enum_declaration(&mut self, declaration: &EnumDeclaration) {
+        self.write("enum ");
+        self.write(&declaration.name.lexeme);
+        self.write(" ");
+        let open = declaration.name.span().end();
+        let close = declaration.span.end() - 1;
+        self.braced(&declaration.variants, open, close, |formatter, variant| {
+            formatter.write(&variant.name.lexeme);
+            if !variant.fields.is_empty() {
+                formatter.write("(");
+                formatter.write(&join_names(
+                    variant.fields.iter().map(|field| field.lexeme.as_str()),
+                ));
+                formatter.write(")");
+            }
+            formatter.write(",");
+        });
+    }
+    /// comma separated expressions
+    fn list(&mut self, exprs: &[Expression]) {
+        for (i, expr) in exprs.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            self.expression(expr);
+        }
+    }
+    fn arguments(&mut self, positional: &[Expression], named: &[(Identifier, Expression)]) {
+        self.write("(");
+        self.list(positional);
+        for (i, (name, value)) in named.iter().enumerate() {
+            if i > 0 || !positional.is_empty() {
+                self.write(", ");
+            }
+            self.write(&name.lexeme);
+            self.write(": ");
+            self.expression(value);
+        }
+        self.write(")");
+    }
+    fn expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Grouping(inner, _) => {
+                self.write("(");
+                self.expression(inner);
+                self.write(")");
+            }
+            Expression::Binary(binary) => {
+                self.node(&binary.lhs);
+                self.write(match binary.op {
+                    BinaryOperation::Add => " + ",
+                    BinaryOperation::Subtract => " - ",
+                    BinaryOperation::Multiply => " * ",
+                    BinaryOperation::Divide => " / ",
+                    BinaryOperation::IntDivide => " ~/ ",
+                    BinaryOperation::Remainder => " % ",
+                    BinaryOperation::BitAnd => " & ",
+                    BinaryOperation::BitOr => " | ",
+                    BinaryOperation::BitXor => " ^ ",
+                    BinaryOperation::ShiftLeft => " << ",
+                    BinaryOperation::ShiftRight => " >> ",
+                });
+                self.node(&binary.rhs);
+            }
+            // written the way they were, `0xff` stays `0xff`
+            Expression::Literal(_, span) => self.write(&self.source[span.range()]),
+            Expression::VariableAssignment(assignment) => {
+                self.write(&assignment.name.lexeme);
+                self.write(" = ");
+                self.expression(&assignment.initializer);
+            }
+            Expression::Not(inner, _) => {
+                self.write("!");
+                self.expression(inner);
+            }
+            Expression::Negate(inner, _) => {
+                self.write("-");
+                self.expression(inner);
+            }
+            Expression::Block(block) => self.block(block),
+            Expression::Identifier(identifier) => self.write(&identifier.lexeme),
+            Expression::If(if_expr) => {
+                self.write("if ");
+                self.expression(&if_expr.predicate);
+                self.write(" ");
+                self.block(&if_expr.then);
+                if let Some(else_block) = &if_expr.else_block {
+                    self.write(" else ");
+                    match &else_block.declarations[..] {
+                        // an else block with only an `if` in it is an `else if`
+                        [inner @ Node::Expression(Expression::If(_))] => self.node(inner),
+                        _ => self.block(else_block),
+                    }
+                }
+            }
+            Expression::Match(match_expr) => self.match_expr(match_expr),
+            Expression::While(while_expr) => {
+                self.write("while ");
+                self.expression(&while_expr.predicate);
+                self.write(" ");
+                self.block(&while_expr.block);
+            }
+            Expression::CallExpr(call) => {
+                self.expression(&call.callee);
+                self.arguments(&call.parameters, &call.named);
+            }
+            Expression::Function(function) => self.function(function),
+            Expression::Comparison(comparison) => {
+                self.expression(&comparison.lhs);
+                self.write(match comparison.kind {
+                    ComparisonKind::GreaterEq => " >= ",
+                    ComparisonKind::LessEq => " <= ",
+                    ComparisonKind::Equal => " == ",
+                    ComparisonKind::NotEqual => " != ",
+                    ComparisonKind::Greater => " > ",
+                    ComparisonKind::Less => " < ",
+                    ComparisonKind::Is => " is ",
+                });
+                self.expression(&comparison.rhs);
+            }
+            Expression::Tuple(elements, _) => {
+                self.write("(");
+                self.list(elements);
+                // `(a)` would just be `a`
+                if elements.len() == 1 {
+                    self.write(",");
+                }
+                self.write(")");
+            }
+            Expression::Array(elements, _) => {
+                self.write("[");
+                self.list(elements);
+                self.write("]");
+            }
+            Expression::Property(property) => {
+                self.expression(&property.object);
+                self.write(".");
+                self.write(&property.name.lexeme);
+            }
+            Expression::SetProperty(property) => {
+                self.expression(&property.object);
+                self.write(".");
+                self.write(&property.name.lexeme);
+                self.write(" = ");
+                self.expression(&property.value);
+            }
+            Expression::Invoke(invoke) => {
+                self.expression(&invoke.object);
+                self.write(".");
+                self.write(&invoke.name.lexeme);
+                self.arguments(&invoke.arguments, &invoke.named);
+            }
+            Expression::SelfRef(_) => self.write("self"),
+        }
+    }
+    fn function(&mut self, function: &FunctionExpr) {
+        if function.token.kind != TokenKind::Pipe {
+            self.write("func");
+            self.parameters(&function.parameters);
+            self.write(" ");
+            self.block(&function.block);
+            return;
+        }
+        // `|x| expr` was parsed into a block returning `expr`
+        self.write("|");
+        self.write(&join_names(
+            function
+                .parameters
+                .iter()
+                .map(|parameter| parameter.name.lexeme.as_str()),
+        ));
+        self.write("| ");
+        if let [Node::Statement(Statement::Return(return_stmt))] =
+            function.block.declarations.as_slice()
+        {
+            if let Some(body) = &return_stmt.expr {
+                self.expression(body);
+            }
+        }
+    }
+    fn match_expr(&mut self, match_expr: &MatchExpr) {
+        self.write("match ");
+        self.expression(&match_expr.value);
+        self.write(" ");
+        let open = match_expr.value.span().end();
+        let close = match_expr.span.end() - 1;
+        self.braced(&match_expr.arms, open, close, |formatter, arm| {
+            formatter.pattern(&arm.pattern);
+            if let Some(guard) = &arm.guard {
+                formatter.write(" if ");
+                formatter.expression(guard);
+            }
+            formatter.write(" => ");
+            formatter.expression(&arm.body);
+            // a comma is optional after `{ }` bodies
+            if !matches!(
+                arm.body,
+                Expression::Block(_) | Expression::If(_) | Expression::While(_)
+            ) {
+                formatter.write(",");
+            }
+        });
+    }
+    fn pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Literal(_, span) | Pattern::Range { span, .. } => {
+                self.write(&self.source[span.range()])
+            }
+            Pattern::Alternatives(alternatives) => {
+                for (i, alternative) in alternatives.iter().enumerate() {
+                    if i > 0 {
+                        self.write(" | ");
+                    }
+                    self.pattern(alternative);
+                }
+            }
+            Pattern::Wildcard => self.write("_"),
+            Pattern::Binding(name) => self.write(&name.lexeme),
+        }
+    }
+}
+
+fn join_names<'n>(names: impl Iterator<Item = &'n str>) -> String {
+    names.collect::<Vec<_>>().join(", ")
+}
@@ -0,0 +1,66 @@
+use crate::{
+    common::{
+        opcode::OpCode,
+        value::{rcrf, AsValue, Value},
+    },
+    frontend::{
+        ast::{identifier::Identifier, CompileToBytecode},
+        compiler::{Compiler, FunctionType},
+        resolver::Resolution,
+        span::Span,
+    },
+};
+
+use super::{function::FunctionDeclaration, AsDeclaration};
+
+/// `class Name { func init(..) {..} func method(..) {..} }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassDeclaration {
+    pub name: Identifier,
+    pub methods: Vec<FunctionDeclaration>,
+    pub span: Span,
+}
+
+impl CompileToBytecode for ClassDeclaration {
+    fn to_bytecode(&self, compiler: &mut Compiler) {
+        let name = compiler
+            .bytecode
+            .function
+            .chunk
+            .emit_value(self.name.lexeme.clone().to_value());
+        compiler
+            .bytecode
+            .function
+            .chunk
+            .emit_op(OpCode::Class(name));
+
+        // the class stays on the stack while its methods are attached
+        for method in &self.methods {
+            let function_type = if method.name.lexeme == "init" {
+                FunctionType::Initializer
+            } else {
+                FunctionType::Method
+            };
+            let function = method.compile_function(compiler, function_type);
+            let chunk = &mut compiler.bytecode.function.chunk;
+            chunk.emit_constant(Value::Function(rcrf(function)));
+            let method_name = chunk.emit_value(method.name.lexeme.clone().to_value());
+            chunk.emit_op(OpCode::Method(method_name));
+        }
+
+        match self.name.resolution {
+            // the name is already in the constant pool for `Class`
+            Resolution::Global => compiler
+                .bytecode
+                .function
+                .chunk
+                .emit_op(OpCode::DefineGlobal(name)),
+            _ => self.name.define(compiler),
+        }
+    }
+}
+impl AsDeclaration for ClassDeclaration {
+    fn to_declaration(self) -> super::Declaration {
+        super::Declaration::ClassDeclaration(self)
+    }
+}
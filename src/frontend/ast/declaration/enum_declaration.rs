@@ -0,0 +1,61 @@
+use crate::{
+    common::{
+        enums::{Enum, Variant},
+        value::{rcrf, Value},
+    },
+    frontend::{
+        ast::{identifier::Identifier, CompileToBytecode},
+        compiler::Compiler,
+        span::Span,
+    },
+};
+
+use super::AsDeclaration;
+
+/// `Circle(r)` in `enum Shape { Circle(r), Empty }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumVariant {
+    pub name: Identifier,
+    pub fields: Vec<Identifier>,
+}
+
+/// `enum Shape { Circle(r), Rect(w, h), Empty }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDeclaration {
+    pub name: Identifier,
+    pub variants: Vec<EnumVariant>,
+    pub span: Span,
+}
+
+impl CompileToBytecode for EnumDeclaration {
+    fn to_bytecode(&self, compiler: &mut Compiler) {
+        // the enum never changes, so it's a plain constant
+        let enumeration = Enum {
+            name: self.name.lexeme.clone(),
+            variants: self
+                .variants
+                .iter()
+                .map(|variant| Variant {
+                    name: variant.name.lexeme.clone(),
+                    fields: variant
+                        .fields
+                        .iter()
+                        .map(|field| field.lexeme.clone())
+                        .collect(),
+                })
+                .collect(),
+        };
+        compiler
+            .bytecode
+            .function
+            .chunk
+            .emit_constant(Value::Enum(rcrf(enumeration)));
+
+        self.name.define(compiler);
+    }
+}
+impl AsDeclaration for EnumDeclaration {
+    fn to_declaration(self) -> super::Declaration {
+        super::Declaration::EnumDeclaration(self)
+    }
+}
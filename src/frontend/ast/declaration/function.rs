@@ -1,56 +1,126 @@
 use crate::{
     common::{
+        function::Function,
         opcode::OpCode,
-        value::{rcrf, AsValue, Value},
+        value::{rcrf, Value},
     },
     frontend::{
-        ast::{expression::block::Block, identifier::Identifier, CompileToBytecode},
+        ast::{
+            expression::{block::Block, Expression},
+            identifier::Identifier,
+            CompileToBytecode,
+        },
         compiler::{Compiler, FunctionType},
+        span::Span,
     },
 };
 
 use super::AsDeclaration;
 
+/// `name`, `name = default` or `...name`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub name: Identifier,
+    /// computed in the function, when the argument isn't given
+    pub default: Option<Expression>,
+    /// collects any extra arguments into an array, only the last one can be
+    pub variadic: bool,
+}
+impl From<Identifier> for Parameter {
+    fn from(name: Identifier) -> Self {
+        Parameter {
+            name,
+            default: None,
+            variadic: false,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct FunctionDeclaration {
     pub name: Identifier,
     pub block: Block,
-    pub parameters: Vec<Identifier>,
+    pub parameters: Vec<Parameter>,
+    /// the `///` comment right above the `func`
+    pub doc: Option<String>,
+    pub span: Span,
 }
 impl<'a> Compiler<'a> {
-    fn in_scope(&self) -> bool {
-        self.bytecode.scope_depth > 0
-    }
-}
-impl CompileToBytecode for FunctionDeclaration {
-    fn to_bytecode(&self, compiler: &mut crate::frontend::compiler::Compiler) {
+    /// compiles a function body into its own function without declaring it anywhere
+    pub fn compile_function(
+        &self,
+        name: String,
+        parameters: &[Parameter],
+        block: &Block,
+        function_type: FunctionType,
+    ) -> Function {
         // uses the current compiler's compilation context for the function
         // which is returned later
         let mut temp_compiler = Compiler::new(
-            compiler.interner.clone(),
-            compiler.diagnostics.clone(),
-            FunctionType::Function,
+            self.interner.clone(),
+            self.diagnostics.clone(),
+            function_type,
         );
-        let function = {
+        temp_compiler.directives = self.directives.clone();
+        {
             // sets the function name and arity
-            temp_compiler.bytecode.function.arity = self.parameters.len() as u8;
-            temp_compiler.bytecode.function.name = self.name.value.lexeme.clone();
+            let function = &mut temp_compiler.bytecode.function;
+            function.variadic = parameters.last().is_some_and(|param| param.variadic);
+            function.arity = (parameters.len() - function.variadic as usize) as u8;
+            function.required = parameters
+                .iter()
+                .take_while(|param| param.default.is_none() && !param.variadic)
+                .count() as u8;
+            function.parameters = parameters
+                .iter()
+                .map(|param| param.name.lexeme.clone())
+                .collect();
+            function.name = name;
 
-            // tells the compiler to recongize any parameters
-            for param in &self.parameters {
-                temp_compiler.add_local(param.value.clone())
+            // arguments that weren't given get their defaults first
+            for (index, param) in parameters.iter().enumerate() {
+                let Some(default) = &param.default else {
+                    continue;
+                };
+                let chunk = &mut temp_compiler.bytecode.function.chunk;
+                let skip = chunk.code.len();
+                chunk.emit_op(OpCode::SkipDefault(index as u16, 0xfff));
+                default.to_bytecode(&mut temp_compiler);
+                let chunk = &mut temp_compiler.bytecode.function.chunk;
+                chunk.emit_op(OpCode::SetLocalConsumes(index as u16));
+                chunk.code[skip] = OpCode::SkipDefault(index as u16, chunk.code.len());
             }
 
             // finally compiles the block
-            self.block.to_bytecode(&mut temp_compiler);
+            block.to_bytecode(&mut temp_compiler);
 
+            // falling off the end returns void, or the instance for initializers
+            let result = match temp_compiler.bytecode.function_type {
+                FunctionType::Initializer => OpCode::GetSelf,
+                _ => OpCode::Void,
+            };
             temp_compiler
                 .bytecode
                 .function
                 .chunk
-                .emit_op(OpCode::Return);
+                .emit_many(vec![result, OpCode::Return]);
             temp_compiler.bytecode.function
-        };
+        }
+    }
+}
+impl FunctionDeclaration {
+    pub fn compile_function(&self, compiler: &Compiler, function_type: FunctionType) -> Function {
+        compiler.compile_function(
+            self.name.lexeme.clone(),
+            &self.parameters,
+            &self.block,
+            function_type,
+        )
+    }
+}
+impl CompileToBytecode for FunctionDeclaration {
+    fn to_bytecode(&self, compiler: &mut crate::frontend::compiler::Compiler) {
+        let function = self.compile_function(compiler, FunctionType::Function);
 
         compiler
             .bytecode
@@ -58,22 +128,7 @@ impl CompileToBytecode for FunctionDeclaration {
             .chunk
             .emit_constant(Value::Function(rcrf(function)));
 
-        if compiler.in_scope() {
-            compiler.add_local(self.name.value.clone());
-        } else {
-            // location of the name in the constant pool
-            let name = compiler
-                .bytecode
-                .function
-                .chunk
-                .emit_value(self.name.value.lexeme.to_value());
-            compiler
-                .bytecode
-                .function
-                .chunk
-                .emit_op(OpCode::DefineGlobal(name))
-        };
-        // compilation context is returned
+        self.name.define(compiler);
     }
 }
 impl AsDeclaration for FunctionDeclaration {
@@ -1,15 +1,24 @@
 use crate::frontend::compiler::Compiler;
 
-use self::{function::FunctionDeclaration, variable_declaration::VariableDeclaration};
+use self::{
+    class::ClassDeclaration, enum_declaration::EnumDeclaration, function::FunctionDeclaration,
+    test::TestDeclaration, variable_declaration::VariableDeclaration,
+};
 
 use super::{node::AsNode, CompileToBytecode};
 
+pub mod class;
+pub mod enum_declaration;
 pub mod function;
+pub mod test;
 pub mod variable_declaration;
 #[derive(Debug, Clone, PartialEq)]
 pub enum Declaration {
     VariableDeclaration(VariableDeclaration),
     FunctionDeclaration(FunctionDeclaration),
+    TestDeclaration(TestDeclaration),
+    ClassDeclaration(ClassDeclaration),
+    EnumDeclaration(EnumDeclaration),
 }
 
 impl CompileToBytecode for Declaration {
@@ -20,6 +29,9 @@ impl CompileToBytecode for Declaration {
             Declaration::FunctionDeclaration(function_declaration) => {
                 function_declaration.to_bytecode(compiler)
             }
+            Declaration::TestDeclaration(test) => test.to_bytecode(compiler),
+            Declaration::ClassDeclaration(class) => class.to_bytecode(compiler),
+            Declaration::EnumDeclaration(enumeration) => enumeration.to_bytecode(compiler),
         }
     }
 }
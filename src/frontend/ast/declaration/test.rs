@@ -0,0 +1,54 @@
+use crate::{
+    common::opcode::OpCode,
+    frontend::{
+        ast::{expression::block::Block, CompileToBytecode},
+        compiler::{Compiler, FunctionType, TestCase},
+        scanner::Token,
+        span::Span,
+    },
+};
+
+use super::AsDeclaration;
+
+/// `test "name" { ... }`, never runs as part of the file, the body is compiled
+/// into its own function and handed to the test runner instead
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestDeclaration {
+    pub name: String,
+    /// the `test` token
+    pub token: Token,
+    pub block: Block,
+    pub span: Span,
+}
+
+impl CompileToBytecode for TestDeclaration {
+    fn to_bytecode(&self, compiler: &mut Compiler) {
+        let mut temp_compiler = Compiler::new(
+            compiler.interner.clone(),
+            compiler.diagnostics.clone(),
+            FunctionType::Function,
+        );
+        temp_compiler.directives = compiler.directives.clone();
+        temp_compiler.bytecode.function.name = self.name.clone();
+
+        self.block.to_bytecode(&mut temp_compiler);
+        temp_compiler
+            .bytecode
+            .function
+            .chunk
+            .emit_many(vec![OpCode::Void, OpCode::Return]);
+
+        let line = compiler.diagnostics.borrow().line(self.token.span);
+        compiler.tests.push(TestCase {
+            name: self.name.clone(),
+            line,
+            function: temp_compiler.bytecode.function,
+        });
+    }
+}
+
+impl AsDeclaration for TestDeclaration {
+    fn to_declaration(self) -> super::Declaration {
+        super::Declaration::TestDeclaration(self)
+    }
+}
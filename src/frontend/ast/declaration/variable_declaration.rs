@@ -1,5 +1,5 @@
 use crate::{
-    common::{opcode::OpCode, value::AsValue},
+    common::opcode::OpCode,
     frontend::{
         ast::{
             expression::Expression,
@@ -8,39 +8,89 @@ use crate::{
             CompileToBytecode,
         },
         compiler::Compiler,
-        scanner::Token,
+        resolver::Resolution,
+        span::Span,
     },
 };
 
 use super::AsDeclaration;
 
+/// what a `let` binds its value to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Binding {
+    /// `let x = ..`
+    Name(Identifier),
+    /// `let (q, r) = ..`
+    Tuple(Vec<Identifier>),
+    /// `let [first, rest...] = ..`, `rest` gets whatever is left as an array
+    Array {
+        names: Vec<Identifier>,
+        rest: Option<Identifier>,
+    },
+}
+impl Binding {
+    /// every name that gets bound, in the order the values end up on the stack
+    pub fn names(&self) -> Vec<&Identifier> {
+        match self {
+            Binding::Name(name) => vec![name],
+            Binding::Tuple(names) => names.iter().collect(),
+            Binding::Array { names, rest } => names.iter().chain(rest).collect(),
+        }
+    }
+    pub fn names_mut(&mut self) -> Vec<&mut Identifier> {
+        match self {
+            Binding::Name(name) => vec![name],
+            Binding::Tuple(names) => names.iter_mut().collect(),
+            Binding::Array { names, rest } => names.iter_mut().chain(rest).collect(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct VariableDeclaration {
-    pub identifier: Identifier,
+    pub binding: Binding,
     pub intializer: Expression,
+    /// the `///` comment right above the `let`
+    pub doc: Option<String>,
+    pub span: Span,
     // pub mutable: bool,
 }
 impl CompileToBytecode for VariableDeclaration {
     fn to_bytecode(&self, compiler: &mut Compiler) {
         self.intializer.to_bytecode(compiler);
-        if compiler.bytecode.scope_depth > 0 {
-            compiler.add_local(self.identifier.value.clone());
-            return;
+        match &self.binding {
+            Binding::Name(_) => {}
+            Binding::Tuple(names) => compiler
+                .bytecode
+                .function
+                .chunk
+                .emit_op(OpCode::Unpack(names.len())),
+            Binding::Array { names, rest: None } => compiler
+                .bytecode
+                .function
+                .chunk
+                .emit_op(OpCode::Unpack(names.len())),
+            Binding::Array {
+                names,
+                rest: Some(_),
+            } => compiler
+                .bytecode
+                .function
+                .chunk
+                .emit_op(OpCode::UnpackRest(names.len())),
         }
-        let function = &mut compiler.bytecode.function;
-        let name = function
-            .chunk
-            .emit_value(self.identifier.value.lexeme.to_value());
-        function.chunk.emit_op(OpCode::DefineGlobal(name))
-    }
-}
-impl<'a> Compiler<'a> {
-    pub fn add_local(&mut self, name: Token) {
-        let local = &mut self.bytecode.locals[self.bytecode.local_count];
-        self.bytecode.local_count += 1;
 
-        local.name = name;
-        local.depth = self.bytecode.scope_depth;
+        let mut names = self.binding.names();
+        // `DefineGlobal` takes the top of the stack, which is the last name
+        if names
+            .first()
+            .is_some_and(|name| name.resolution == Resolution::Global)
+        {
+            names.reverse();
+        }
+        for name in names {
+            name.define(compiler);
+        }
     }
 }
 impl AsDeclaration for VariableDeclaration {
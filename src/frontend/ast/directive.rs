@@ -0,0 +1,51 @@
+use crate::{
+    common::value::Value,
+    frontend::{compiler::Compiler, span::Span},
+};
+
+use super::{
+    expression::Expression,
+    identifier::Identifier,
+    node::{AsNode, Node},
+    CompileToBytecode,
+};
+
+/// `#name` or `#name [ ... ]`, what it compiles to is up to the directive
+/// registered under that name, see [`crate::frontend::compiler::directives`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Directive {
+    /// the identifier after the `#`
+    pub name: Identifier,
+    pub arguments: Vec<Expression>,
+    pub span: Span,
+}
+
+impl Directive {
+    pub fn name(&self) -> &str {
+        &self.name.lexeme
+    }
+    /// the arguments folded to values, only valid for directives that take
+    /// [`crate::frontend::compiler::directives::Arguments::Constants`]
+    pub fn constants(&self) -> Vec<Value> {
+        self.arguments
+            .iter()
+            .map(|expr| expr.to_constant().expect("checked by the parser"))
+            .collect()
+    }
+}
+
+impl CompileToBytecode for Directive {
+    fn to_bytecode(&self, compiler: &mut Compiler) {
+        let definition = compiler
+            .directives
+            .get(self.name())
+            .unwrap_or_else(|| panic!("directive #{} was never registered", self.name()));
+        (definition.emit)(self, compiler)
+    }
+}
+
+impl AsNode for Directive {
+    fn to_node(self) -> Node {
+        Node::Directive(self)
+    }
+}
@@ -0,0 +1,365 @@
+//! turns a parsed file into a tree of labelled nodes, printed indented for
+//! people or as json for tools, used by `--emit=ast`
+
+use std::fmt::Write;
+
+use serde_json::{json, Value};
+
+use crate::frontend::span::{LineIndex, Span};
+
+use super::{
+    declaration::{
+        class::ClassDeclaration,
+        enum_declaration::EnumDeclaration,
+        function::{FunctionDeclaration, Parameter},
+        test::TestDeclaration,
+        variable_declaration::{Binding, VariableDeclaration},
+    },
+    directive::Directive,
+    expression::{
+        block::Block,
+        call_expr::CallExpr,
+        comparison::Comparison,
+        function_expr::FunctionExpr,
+        if_expr::IfExpr,
+        match_expr::{MatchArm, MatchExpr, Pattern},
+        property::{Invoke, Property, SetProperty},
+        variable_assignment::VariableAssignment,
+        while_expr::WhileExpr,
+        BinaryExpr, Expression,
+    },
+    identifier::Identifier,
+    literal::Literal,
+    node::{AstPosition, Node},
+    statement::{assertion::Assertion, return_stmt::ReturnStmt, try_catch::TryCatch, Statement},
+    visit::{self, Visitor},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AstTree {
+    /// what kind of node this is, `If`, `Call`, ..
+    pub label: &'static str,
+    /// names, operators and values, whatever tells two nodes of a kind apart
+    pub detail: Option<String>,
+    /// `None` for the few nodes that don't keep one, like pattern literals
+    pub span: Option<Span>,
+    pub children: Vec<AstTree>,
+}
+impl AstTree {
+    pub fn from_nodes(nodes: &[Node]) -> AstTree {
+        let mut dump = AstDump {
+            stack: vec![AstTree::new("File", None, None)],
+        };
+        for node in nodes {
+            dump.visit_node(node);
+        }
+        dump.stack.pop().unwrap()
+    }
+    fn new(label: &'static str, detail: Option<String>, span: Option<Span>) -> AstTree {
+        AstTree {
+            label,
+            detail,
+            span,
+            children: Vec::new(),
+        }
+    }
+    /// one node per line, children indented under their parent and every
+    /// span shown as `line:column..line:column`
+    pub fn to_text(&self, lines: &LineIndex) -> String {
+        let mut text = String::new();
+        self.write_text(lines, 0, &mut text);
+        text
+    }
+    fn write_text(&self, lines: &LineIndex, depth: usize, text: &mut String) {
+        write!(text, "{}{}", "  ".repeat(depth), self.label).unwrap();
+        if let Some(detail) = &self.detail {
+            write!(text, " {detail}").unwrap();
+        }
+        if let Some(span) = self.span {
+            let start = lines.position(span.start);
+            let end = lines.position(span.end() as u32);
+            write!(
+                text,
+                " ({}:{}..{}:{})",
+                start.line + 1,
+                start.column + 1,
+                end.line + 1,
+                end.column + 1
+            )
+            .unwrap();
+        }
+        text.push('\n');
+        for child in &self.children {
+            child.write_text(lines, depth + 1, text);
+        }
+    }
+    /// `{"kind", "detail", "span": {"start", "end"}, "children"}`, spans are
+    /// byte offsets and `detail`/`span` are left out when there isn't one
+    pub fn to_json(&self) -> Value {
+        let mut json = json!({ "kind": self.label });
+        if let Some(detail) = &self.detail {
+            json["detail"] = json!(detail);
+        }
+        if let Some(span) = self.span {
+            json["span"] = json!({ "start": span.start, "end": span.end() });
+        }
+        json["children"] = self.children.iter().map(AstTree::to_json).collect();
+        json
+    }
+}
+
+struct AstDump {
+    /// the nodes being visited, the file at the bottom
+    stack: Vec<AstTree>,
+}
+impl AstDump {
+    fn node(
+        &mut self,
+        label: &'static str,
+        detail: Option<String>,
+        span: Option<Span>,
+        children: impl FnOnce(&mut AstDump),
+    ) {
+        self.stack.push(AstTree::new(label, detail, span));
+        children(self);
+        let tree = self.stack.pop().unwrap();
+        self.stack.last_mut().unwrap().children.push(tree);
+    }
+    /// `name: value` arguments, which the visitors walk as bare values
+    fn named_arguments(&mut self, named: &[(Identifier, Expression)]) {
+        for (name, value) in named {
+            let span = name.span().to(value.span());
+            self.node(
+                "NamedArgument",
+                Some(name.lexeme.clone()),
+                Some(span),
+                |this| this.visit_expression(value),
+            );
+        }
+    }
+}
+fn literal_detail(literal: &Literal) -> String {
+    match literal {
+        Literal::Number(number) => format!("{number:?}"),
+        Literal::Int(int) => int.to_string(),
+        Literal::String(string) => format!("{string:?}"),
+        Literal::Bool(bool) => bool.to_string(),
+        Literal::Void => String::from("void"),
+    }
+}
+impl Visitor for AstDump {
+    fn visit_expression(&mut self, expr: &Expression) {
+        let span = Some(expr.span());
+        match expr {
+            Expression::Grouping(..) => self.node("Grouping", None, span, |this| {
+                visit::walk_expression(this, expr)
+            }),
+            Expression::Not(..) => {
+                self.node("Not", None, span, |this| visit::walk_expression(this, expr))
+            }
+            Expression::Negate(..) => self.node("Negate", None, span, |this| {
+                visit::walk_expression(this, expr)
+            }),
+            Expression::Tuple(..) => self.node("Tuple", None, span, |this| {
+                visit::walk_expression(this, expr)
+            }),
+            Expression::Array(..) => self.node("Array", None, span, |this| {
+                visit::walk_expression(this, expr)
+            }),
+            Expression::Literal(literal, _) => {
+                self.node("Literal", Some(literal_detail(literal)), span, |_| {})
+            }
+            Expression::SelfRef(_) => self.node("SelfRef", None, span, |_| {}),
+            _ => visit::walk_expression(self, expr),
+        }
+    }
+    fn visit_statement(&mut self, statement: &Statement) {
+        let span = Some(statement.span());
+        match statement {
+            Statement::Print(..) => self.node("Print", None, span, |this| {
+                visit::walk_statement(this, statement)
+            }),
+            Statement::Throw(..) => self.node("Throw", None, span, |this| {
+                visit::walk_statement(this, statement)
+            }),
+            _ => visit::walk_statement(self, statement),
+        }
+    }
+    fn visit_identifier(&mut self, identifier: &Identifier) {
+        self.node(
+            "Identifier",
+            Some(identifier.lexeme.clone()),
+            Some(identifier.span()),
+            |_| {},
+        )
+    }
+    fn visit_literal(&mut self, literal: &Literal) {
+        self.node("Literal", Some(literal_detail(literal)), None, |_| {})
+    }
+    fn visit_directive(&mut self, directive: &Directive) {
+        let name = Some(directive.name().to_owned());
+        self.node("Directive", name, Some(directive.span), |this| {
+            visit::walk_directive(this, directive)
+        })
+    }
+    fn visit_block(&mut self, block: &Block) {
+        self.node("Block", None, Some(block.span), |this| {
+            visit::walk_block(this, block)
+        })
+    }
+    fn visit_binary(&mut self, binary: &BinaryExpr) {
+        let op = Some(format!("{:?}", binary.op));
+        self.node("Binary", op, Some(binary.span), |this| {
+            visit::walk_binary(this, binary)
+        })
+    }
+    fn visit_variable_assignment(&mut self, assignment: &VariableAssignment) {
+        self.node("VariableAssignment", None, Some(assignment.span), |this| {
+            visit::walk_variable_assignment(this, assignment)
+        })
+    }
+    fn visit_if(&mut self, if_expr: &IfExpr) {
+        self.node("If", None, Some(if_expr.span), |this| {
+            visit::walk_if(this, if_expr)
+        })
+    }
+    fn visit_while(&mut self, while_expr: &WhileExpr) {
+        self.node("While", None, Some(while_expr.span), |this| {
+            visit::walk_while(this, while_expr)
+        })
+    }
+    fn visit_match(&mut self, match_expr: &MatchExpr) {
+        self.node("Match", None, Some(match_expr.span), |this| {
+            visit::walk_match(this, match_expr)
+        })
+    }
+    fn visit_match_arm(&mut self, arm: &MatchArm) {
+        self.node("MatchArm", None, Some(arm.span), |this| {
+            visit::walk_match_arm(this, arm)
+        })
+    }
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        let detail = match pattern {
+            Pattern::Literal(..) => "literal",
+            Pattern::Alternatives(_) => "alternatives",
+            Pattern::Range {
+                inclusive: true, ..
+            } => "..=",
+            Pattern::Range { .. } => "..",
+            Pattern::Wildcard => "_",
+            Pattern::Binding(_) => "binding",
+        };
+        self.node("Pattern", Some(detail.to_owned()), None, |this| {
+            visit::walk_pattern(this, pattern)
+        })
+    }
+    fn visit_call(&mut self, call: &CallExpr) {
+        self.node("Call", None, Some(call.span), |this| {
+            this.visit_expression(&call.callee);
+            for argument in call.parameters.iter() {
+                this.visit_expression(argument);
+            }
+            this.named_arguments(&call.named);
+        })
+    }
+    fn visit_function(&mut self, function: &FunctionExpr) {
+        self.node("Function", None, Some(function.span), |this| {
+            visit::walk_function(this, function)
+        })
+    }
+    fn visit_parameter(&mut self, parameter: &Parameter) {
+        let detail = parameter.variadic.then(|| String::from("variadic"));
+        self.node("Parameter", detail, Some(parameter.name.span()), |this| {
+            visit::walk_parameter(this, parameter)
+        })
+    }
+    fn visit_comparison(&mut self, comparison: &Comparison) {
+        let kind = Some(format!("{:?}", comparison.kind));
+        self.node("Comparison", kind, Some(comparison.span), |this| {
+            visit::walk_comparison(this, comparison)
+        })
+    }
+    fn visit_property(&mut self, property: &Property) {
+        let name = Some(property.name.lexeme.clone());
+        self.node("Property", name, Some(property.span), |this| {
+            visit::walk_property(this, property)
+        })
+    }
+    fn visit_set_property(&mut self, property: &SetProperty) {
+        let name = Some(property.name.lexeme.clone());
+        self.node("SetProperty", name, Some(property.span), |this| {
+            visit::walk_set_property(this, property)
+        })
+    }
+    fn visit_invoke(&mut self, invoke: &Invoke) {
+        let name = Some(invoke.name.lexeme.clone());
+        self.node("Invoke", name, Some(invoke.span), |this| {
+            this.visit_expression(&invoke.object);
+            for argument in &invoke.arguments {
+                this.visit_expression(argument);
+            }
+            this.named_arguments(&invoke.named);
+        })
+    }
+    fn visit_assertion(&mut self, assertion: &Assertion) {
+        let kind = Some(format!("{:?}", assertion.kind));
+        self.node("Assertion", kind, Some(assertion.span), |this| {
+            visit::walk_assertion(this, assertion)
+        })
+    }
+    fn visit_return(&mut self, return_stmt: &ReturnStmt) {
+        self.node("Return", None, Some(return_stmt.span), |this| {
+            visit::walk_return(this, return_stmt)
+        })
+    }
+    fn visit_try_catch(&mut self, try_catch: &TryCatch) {
+        self.node("TryCatch", None, Some(try_catch.span), |this| {
+            visit::walk_try_catch(this, try_catch)
+        })
+    }
+    fn visit_variable_declaration(&mut self, declaration: &VariableDeclaration) {
+        self.node("Let", None, Some(declaration.span), |this| {
+            visit::walk_variable_declaration(this, declaration)
+        })
+    }
+    fn visit_binding(&mut self, binding: &Binding) {
+        let detail = match binding {
+            Binding::Name(_) => "name",
+            Binding::Tuple(_) => "tuple",
+            Binding::Array { .. } => "array",
+        };
+        self.node("Binding", Some(detail.to_owned()), None, |this| {
+            visit::walk_binding(this, binding)
+        })
+    }
+    fn visit_function_declaration(&mut self, declaration: &FunctionDeclaration) {
+        self.node(
+            "FunctionDeclaration",
+            None,
+            Some(declaration.span),
+            |this| visit::walk_function_declaration(this, declaration),
+        )
+    }
+    fn visit_test_declaration(&mut self, declaration: &TestDeclaration) {
+        let name = Some(format!("{:?}", declaration.name));
+        self.node("Test", name, Some(declaration.span), |this| {
+            visit::walk_test_declaration(this, declaration)
+        })
+    }
+    fn visit_class_declaration(&mut self, declaration: &ClassDeclaration) {
+        self.node("Class", None, Some(declaration.span), |this| {
+            visit::walk_class_declaration(this, declaration)
+        })
+    }
+    fn visit_enum_declaration(&mut self, declaration: &EnumDeclaration) {
+        let variants = declaration
+            .variants
+            .iter()
+            .map(|variant| variant.name.lexeme.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.node("Enum", Some(variants), Some(declaration.span), |this| {
+            visit::walk_enum_declaration(this, declaration)
+        })
+    }
+}
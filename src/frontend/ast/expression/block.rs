@@ -6,6 +6,7 @@ use crate::{
             CompileToBytecode,
         },
         compiler::Compiler,
+        span::Span,
     },
 };
 
@@ -14,28 +15,25 @@ use super::AsExpr;
 #[derive(Debug, PartialEq, Clone)]
 pub struct Block {
     pub declarations: Vec<Node>,
+    pub span: Span,
+    /// how many locals are declared right in it, filled in by the
+    /// [resolver](crate::frontend::resolver) so they can be popped at its end
+    pub locals: u16,
 }
 
 impl CompileToBytecode for Block {
     fn to_bytecode(&self, compiler: &mut crate::frontend::compiler::Compiler) {
-        compiler.begin_scope();
         for dec in &self.declarations {
             dec.to_bytecode(compiler)
         }
-        compiler.end_scope();
+        compiler.pop_locals(self.locals);
     }
 }
 impl<'a> Compiler<'a> {
-    pub fn begin_scope(&mut self) {
-        self.bytecode.scope_depth += 1;
-    }
-    pub fn end_scope(&mut self) {
-        self.bytecode.scope_depth -= 1;
-        while self.bytecode.local_count > 0
-            && self.bytecode.locals[self.bytecode.local_count - 1].depth > self.bytecode.scope_depth
-        {
+    /// pops the locals of a scope that ended
+    pub fn pop_locals(&mut self, count: u16) {
+        for _ in 0..count {
             self.bytecode.function.chunk.emit_op(OpCode::Pop);
-            self.bytecode.local_count -= 1;
         }
         self.bytecode
             .function
@@ -1,25 +1,58 @@
 use crate::{
-    common::opcode::OpCode,
-    frontend::ast::{identifier::Identifier, CompileToBytecode},
+    common::{
+        opcode::OpCode,
+        value::{rcrf, AsValue, Value},
+    },
+    frontend::{
+        ast::{identifier::Identifier, CompileToBytecode},
+        compiler::Compiler,
+        span::Span,
+    },
 };
 
 use super::{AsExpr, Expression};
+/// `callee(parameters)`, the callee can be any expression that evaluates to
+/// something callable
 #[derive(Debug, Clone, PartialEq)]
 pub struct CallExpr {
-    pub identifier: Identifier,
+    pub callee: Box<Expression>,
     pub parameters: Box<Vec<Expression>>,
+    /// `name: value` arguments, they always come after the positional ones
+    pub named: Vec<(Identifier, Expression)>,
+    pub span: Span,
 }
 impl CompileToBytecode for CallExpr {
     fn to_bytecode(&self, compiler: &mut crate::frontend::compiler::Compiler) {
-        self.identifier.to_bytecode(compiler);
-        self.parameters
-            .iter()
-            .for_each(|param| param.clone().to_bytecode(compiler));
-        compiler
-            .bytecode
-            .function
-            .chunk
-            .emit_op(OpCode::Call(self.parameters.len()));
+        self.callee.to_bytecode(compiler);
+        for param in self.parameters.iter() {
+            param.to_bytecode(compiler);
+        }
+        compiler.emit_call(self.parameters.len(), &self.named);
+    }
+}
+impl<'a> Compiler<'a> {
+    /// compiles the named arguments, which go after the callee and the
+    /// `positional` ones that are already on the stack, and then calls
+    pub fn emit_call(&mut self, positional: usize, named: &[(Identifier, Expression)]) {
+        for (_, value) in named {
+            value.to_bytecode(self);
+        }
+        let arg_count = positional + named.len();
+        let call = if named.is_empty() {
+            OpCode::Call(arg_count)
+        } else {
+            let names = named
+                .iter()
+                .map(|(name, _)| name.lexeme.as_str().to_value())
+                .collect();
+            let names = self
+                .bytecode
+                .function
+                .chunk
+                .emit_value(Value::Array(rcrf(names)));
+            OpCode::CallNamed(arg_count, names)
+        };
+        self.bytecode.function.chunk.emit_op(call);
     }
 }
 impl AsExpr for CallExpr {
@@ -1,4 +1,7 @@
-use crate::{common::opcode::OpCode, frontend::ast::CompileToBytecode};
+use crate::{
+    common::opcode::OpCode,
+    frontend::{ast::CompileToBytecode, span::Span},
+};
 
 use super::{AsExpr, Expression};
 
@@ -10,12 +13,15 @@ pub enum ComparisonKind {
     NotEqual,
     Greater,
     Less,
+    /// `value is Enum.Variant`
+    Is,
 }
 #[derive(Debug, Clone, PartialEq)]
 pub struct Comparison {
     pub kind: ComparisonKind,
     pub lhs: Box<Expression>,
     pub rhs: Box<Expression>,
+    pub span: Span,
 }
 impl AsExpr for Comparison {
     fn to_expr(self) -> Expression {
@@ -34,6 +40,7 @@ impl CompileToBytecode for Comparison {
             ComparisonKind::Equal => emit_op(OpCode::Equal),
             ComparisonKind::NotEqual => emit_op(OpCode::NotEqual),
             ComparisonKind::Less => emit_op(OpCode::Less),
+            ComparisonKind::Is => emit_op(OpCode::Is),
         }
     }
 }
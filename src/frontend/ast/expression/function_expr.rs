@@ -0,0 +1,43 @@
+use crate::{
+    common::value::{rcrf, Value},
+    frontend::{
+        ast::{declaration::function::Parameter, CompileToBytecode},
+        compiler::{Compiler, FunctionType},
+        scanner::Token,
+        span::Span,
+    },
+};
+
+use super::{block::Block, AsExpr, Expression};
+
+/// `func(x) { ... }` or `|x| expr`, leaves the function on the stack
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionExpr {
+    /// the `func` or the opening `|` token
+    pub token: Token,
+    pub parameters: Vec<Parameter>,
+    /// `|x| expr` is stored as a block returning `expr`
+    pub block: Block,
+    pub span: Span,
+}
+
+impl CompileToBytecode for FunctionExpr {
+    fn to_bytecode(&self, compiler: &mut Compiler) {
+        let function = compiler.compile_function(
+            "anonymous".to_string(),
+            &self.parameters,
+            &self.block,
+            FunctionType::Function,
+        );
+        compiler
+            .bytecode
+            .function
+            .chunk
+            .emit_constant(Value::Function(rcrf(function)));
+    }
+}
+impl AsExpr for FunctionExpr {
+    fn to_expr(self) -> Expression {
+        Expression::Function(self)
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     common::opcode::OpCode,
-    frontend::{ast::CompileToBytecode, compiler::Compiler},
+    frontend::{ast::CompileToBytecode, compiler::Compiler, span::Span},
 };
 
 use super::{block::Block, AsExpr, Expression};
@@ -10,6 +10,7 @@ pub struct IfExpr {
     pub predicate: Box<Expression>,
     pub then: Block,
     pub else_block: Option<Block>,
+    pub span: Span,
 }
 impl AsExpr for IfExpr {
     fn to_expr(self) -> super::Expression {
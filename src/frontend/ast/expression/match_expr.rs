@@ -0,0 +1,220 @@
+use crate::{
+    common::opcode::OpCode,
+    frontend::{
+        ast::{identifier::Identifier, literal::Literal, CompileToBytecode},
+        compiler::Compiler,
+        scanner::Token,
+        span::Span,
+    },
+};
+
+use super::{AsExpr, Expression};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    /// `1`, `"lime"`, `true`
+    Literal(Literal, Span),
+    /// `1 | 2 | 3`
+    Alternatives(Vec<Pattern>),
+    /// `1..5`, or `1..=5` when `inclusive`
+    Range {
+        start: Literal,
+        end: Literal,
+        inclusive: bool,
+        span: Span,
+    },
+    /// `_`
+    Wildcard,
+    /// any other name, matches anything and binds it for the guard and body
+    Binding(Identifier),
+}
+impl Pattern {
+    /// whether the pattern matches every value
+    pub fn is_irrefutable(&self) -> bool {
+        match self {
+            Pattern::Wildcard | Pattern::Binding(_) => true,
+            Pattern::Alternatives(alternatives) => alternatives.iter().any(Pattern::is_irrefutable),
+            Pattern::Literal(..) | Pattern::Range { .. } => false,
+        }
+    }
+}
+
+/// `pattern if guard => body`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expression>,
+    pub body: Expression,
+    pub span: Span,
+}
+
+/// `match value { pattern => body, ... }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchExpr {
+    /// the `match` token
+    pub token: Token,
+    pub value: Box<Expression>,
+    pub arms: Vec<MatchArm>,
+    pub span: Span,
+    /// the hidden local the matched value is kept in, given by the
+    /// [resolver](crate::frontend::resolver)
+    pub slot: u16,
+}
+impl MatchExpr {
+    /// a match without an arm that always matches can fall through at runtime
+    pub fn is_exhaustive(&self) -> bool {
+        self.arms
+            .iter()
+            .any(|arm| arm.guard.is_none() && arm.pattern.is_irrefutable())
+    }
+}
+
+impl<'a> Compiler<'a> {
+    fn emit_jump(&mut self, op: OpCode) -> usize {
+        let jump = self.bytecode.function.chunk.code.len();
+        self.bytecode.function.chunk.emit_op(op);
+        jump
+    }
+    fn patch_jump(&mut self, jump: usize) {
+        let target = self.bytecode.function.chunk.code.len();
+        self.bytecode.function.chunk.code[jump] = match self.bytecode.function.chunk.code[jump] {
+            OpCode::JumpTo(_) => OpCode::JumpTo(target),
+            OpCode::JumpToIfFalse(_) => OpCode::JumpToIfFalse(target),
+            OpCode::PopJumpToIfFalse(_) => OpCode::PopJumpToIfFalse(target),
+            ref op => unreachable!("{op} is not a jump"),
+        }
+    }
+    /// leaves whether the matched value (in local `value`) fits the pattern on
+    /// the stack, bindings are handled by the arm
+    fn emit_pattern_test(&mut self, pattern: &Pattern, value: u16) {
+        let chunk = &mut self.bytecode.function.chunk;
+        match pattern {
+            Pattern::Wildcard | Pattern::Binding(_) => {
+                chunk.emit_op(OpCode::True);
+            }
+            Pattern::Literal(literal, _) => {
+                chunk.emit_op(OpCode::GetLocal(value));
+                literal.to_bytecode(self);
+                self.bytecode.function.chunk.emit_op(OpCode::Equal);
+            }
+            Pattern::Range {
+                start,
+                end,
+                inclusive,
+                ..
+            } => {
+                // only numbers can be compared with the bounds
+                chunk.emit_op(OpCode::GetLocal(value));
+                chunk.emit_op(OpCode::IsNumber);
+                let not_number = self.emit_jump(OpCode::JumpToIfFalse(0xfff));
+
+                let chunk = &mut self.bytecode.function.chunk;
+                chunk.emit_op(OpCode::Pop);
+                chunk.emit_op(OpCode::GetLocal(value));
+                start.to_bytecode(self);
+                self.bytecode.function.chunk.emit_op(OpCode::GreaterEq);
+                let below_start = self.emit_jump(OpCode::JumpToIfFalse(0xfff));
+
+                let chunk = &mut self.bytecode.function.chunk;
+                chunk.emit_op(OpCode::Pop);
+                chunk.emit_op(OpCode::GetLocal(value));
+                end.to_bytecode(self);
+                self.bytecode.function.chunk.emit_op(if *inclusive {
+                    OpCode::LessEq
+                } else {
+                    OpCode::Less
+                });
+                self.patch_jump(not_number);
+                self.patch_jump(below_start);
+            }
+            Pattern::Alternatives(alternatives) => {
+                // the first alternative that matches skips the rest with `true`
+                // still on the stack
+                let mut matched = Vec::new();
+                for (i, alternative) in alternatives.iter().enumerate() {
+                    self.emit_pattern_test(alternative, value);
+                    if i + 1 == alternatives.len() {
+                        break;
+                    }
+                    let next = self.emit_jump(OpCode::JumpToIfFalse(0xfff));
+                    matched.push(self.emit_jump(OpCode::JumpTo(0xfff)));
+                    self.patch_jump(next);
+                    self.bytecode.function.chunk.emit_op(OpCode::Pop);
+                }
+                for jump in matched {
+                    self.patch_jump(jump);
+                }
+            }
+        }
+    }
+}
+
+impl CompileToBytecode for MatchExpr {
+    fn to_bytecode(&self, compiler: &mut Compiler) {
+        // the matched value lives in a hidden local, the result of the arm
+        // that runs ends up in its slot
+        self.value.to_bytecode(compiler);
+        let value = self.slot;
+
+        let mut to_end = Vec::new();
+        for arm in &self.arms {
+            let binding = match &arm.pattern {
+                Pattern::Binding(name) => {
+                    compiler
+                        .bytecode
+                        .function
+                        .chunk
+                        .emit_op(OpCode::GetLocal(value));
+                    name.define(compiler);
+                    true
+                }
+                _ => false,
+            };
+
+            // each failed test jumps straight to the next arm
+            let mut to_next_arm = Vec::new();
+            if !arm.pattern.is_irrefutable() {
+                compiler.emit_pattern_test(&arm.pattern, value);
+                to_next_arm.push(compiler.emit_jump(OpCode::PopJumpToIfFalse(0xfff)));
+            }
+            if let Some(guard) = &arm.guard {
+                guard.to_bytecode(compiler);
+                to_next_arm.push(compiler.emit_jump(OpCode::PopJumpToIfFalse(0xfff)));
+            }
+
+            arm.body.to_bytecode(compiler);
+            if matches!(
+                arm.body,
+                Expression::Block(_) | Expression::If(_) | Expression::While(_)
+            ) {
+                // these don't leave a value behind
+                compiler.bytecode.function.chunk.emit_op(OpCode::Void);
+            }
+            compiler
+                .bytecode
+                .function
+                .chunk
+                .emit_op(OpCode::SetLocalConsumes(value));
+            compiler.pop_locals(binding as u16);
+            to_end.push(compiler.emit_jump(OpCode::JumpTo(0xfff)));
+
+            for jump in to_next_arm {
+                compiler.patch_jump(jump);
+            }
+            if binding {
+                compiler.bytecode.function.chunk.emit_op(OpCode::Pop);
+            }
+        }
+        if !self.is_exhaustive() {
+            compiler.bytecode.function.chunk.emit_op(OpCode::NoMatch);
+        }
+        for jump in to_end {
+            compiler.patch_jump(jump);
+        }
+    }
+}
+impl AsExpr for MatchExpr {
+    fn to_expr(self) -> Expression {
+        Expression::Match(self)
+    }
+}
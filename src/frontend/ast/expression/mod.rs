@@ -1,14 +1,26 @@
-use crate::{common::opcode::OpCode, frontend::compiler::Compiler};
+use std::rc::Rc;
+
+use crate::{
+    common::{opcode::OpCode, value::Value},
+    frontend::{compiler::Compiler, scanner::Token, span::Span},
+};
 
 use self::{
-    block::Block, call_expr::CallExpr, comparison::Comparison, if_expr::IfExpr,
-    variable_assignment::VariableAssignment, while_expr::WhileExpr,
+    block::Block,
+    call_expr::CallExpr,
+    comparison::Comparison,
+    function_expr::FunctionExpr,
+    if_expr::IfExpr,
+    match_expr::MatchExpr,
+    property::{Invoke, Property, SetProperty},
+    variable_assignment::VariableAssignment,
+    while_expr::WhileExpr,
 };
 
 use super::{
     identifier::Identifier,
     literal::Literal,
-    node::{AsNode, Node},
+    node::{AsNode, AstPosition, Node},
     BinaryOperation, CompileToBytecode,
 };
 pub trait AsExpr {
@@ -17,7 +29,10 @@ pub trait AsExpr {
 pub mod block;
 pub mod call_expr;
 pub mod comparison;
+pub mod function_expr;
 pub mod if_expr;
+pub mod match_expr;
+pub mod property;
 pub mod variable_assignment;
 pub mod while_expr;
 #[derive(Debug, PartialEq, Clone)]
@@ -25,6 +40,7 @@ pub struct BinaryExpr {
     pub lhs: Box<Node>,
     pub rhs: Box<Node>,
     pub op: BinaryOperation,
+    pub span: Span,
 }
 impl AsExpr for BinaryExpr {
     fn to_expr(self) -> Expression {
@@ -33,18 +49,30 @@ impl AsExpr for BinaryExpr {
 }
 #[derive(Debug, PartialEq, Clone)]
 pub enum Expression {
-    Grouping(Box<Expression>),
+    /// `(expr)`, the span includes the parentheses
+    Grouping(Box<Expression>, Span),
     Binary(BinaryExpr),
-    Literal(Literal),
+    Literal(Literal, Span),
     VariableAssignment(VariableAssignment),
-    Not(Box<Expression>),
-    Negate(Box<Expression>),
+    Not(Box<Expression>, Span),
+    Negate(Box<Expression>, Span),
     Block(Block),
     Identifier(Identifier),
     If(IfExpr),
+    Match(MatchExpr),
     While(WhileExpr),
     CallExpr(CallExpr),
+    Function(FunctionExpr),
     Comparison(Comparison),
+    /// `(a, b)`
+    Tuple(Vec<Expression>, Span),
+    /// `[a, b]`
+    Array(Vec<Expression>, Span),
+    Property(Property),
+    SetProperty(SetProperty),
+    Invoke(Invoke),
+    /// `self` inside of a method
+    SelfRef(Token),
 }
 impl AsNode for Expression {
     fn to_node(self) -> Node {
@@ -52,8 +80,33 @@ impl AsNode for Expression {
     }
 }
 impl Expression {
+    /// folds the expression into a value if it can be known at compile time
+    pub fn to_constant(&self) -> Option<Value> {
+        match self {
+            Expression::Literal(literal, _) => Some(literal.clone().into()),
+            Expression::Grouping(inner, _) => inner.to_constant(),
+            Expression::Negate(inner, _) => match inner.to_constant()? {
+                Value::Number(number) => Some(Value::Number(-number)),
+                Value::Int(int) => int.checked_neg().map(Value::Int),
+                _ => None,
+            },
+            Expression::Not(inner, _) => match inner.to_constant()? {
+                Value::Boolean(bool) => Some(Value::Boolean(!bool)),
+                _ => None,
+            },
+            Expression::Tuple(elements, _) => Some(Value::Tuple(Rc::new(
+                elements
+                    .iter()
+                    .map(Expression::to_constant)
+                    .collect::<Option<_>>()?,
+            ))),
+            _ => None,
+        }
+    }
     pub fn as_literal(self) -> Literal {
-        let Expression::Literal(literal) = self else {panic!()};
+        let Expression::Literal(literal, _) = self else {
+            panic!()
+        };
         literal
     }
 }
@@ -74,26 +127,42 @@ impl Expression {
 }
 impl CompileToBytecode for Expression {
     fn to_bytecode(&self, compiler: &mut Compiler) {
+        let start = compiler.bytecode.function.chunk.code.len();
         match self {
             Expression::CallExpr(call_expr) => call_expr.to_bytecode(compiler),
+            Expression::Function(function) => function.to_bytecode(compiler),
             Expression::While(while_expr) => while_expr.to_bytecode(compiler),
-            Expression::Grouping(inner) => inner.to_bytecode(compiler),
-            Expression::Literal(literal) => literal.to_bytecode(compiler),
+            Expression::Grouping(inner, _) => inner.to_bytecode(compiler),
+            Expression::Literal(literal, _) => literal.to_bytecode(compiler),
             Expression::VariableAssignment(var) => var.to_bytecode(compiler),
-            Expression::Not(expr) => {
+            Expression::Not(expr, _) => {
                 expr.to_bytecode(compiler);
                 compiler.bytecode.function.chunk.emit_op(OpCode::Not);
             }
             Expression::If(if_expr) => if_expr.to_bytecode(compiler),
-            Expression::Negate(expr) => {
+            Expression::Match(match_expr) => match_expr.to_bytecode(compiler),
+            Expression::Negate(expr, _) => {
                 expr.to_bytecode(compiler);
                 compiler.bytecode.function.chunk.emit_op(OpCode::Negate);
             }
             Expression::Comparison(comparison) => comparison.to_bytecode(compiler),
             Expression::Block(block) => block.to_bytecode(compiler),
             Expression::Identifier(identifier) => identifier.to_bytecode(compiler),
+            Expression::Tuple(elements, _) | Expression::Array(elements, _) => {
+                for element in elements {
+                    element.to_bytecode(compiler);
+                }
+                compiler.bytecode.function.chunk.emit_op(match self {
+                    Expression::Tuple(_, _) => OpCode::Tuple(elements.len()),
+                    _ => OpCode::Array(elements.len()),
+                });
+            }
+            Expression::Property(property) => property.to_bytecode(compiler),
+            Expression::SetProperty(property) => property.to_bytecode(compiler),
+            Expression::Invoke(invoke) => invoke.to_bytecode(compiler),
+            Expression::SelfRef(_) => compiler.bytecode.function.chunk.emit_op(OpCode::GetSelf),
             super::Expression::Binary(binary) => {
-                let BinaryExpr { lhs, rhs, op } = binary;
+                let BinaryExpr { lhs, rhs, op, .. } = binary;
                 lhs.to_bytecode(compiler);
                 rhs.to_bytecode(compiler);
 
@@ -103,8 +172,16 @@ impl CompileToBytecode for Expression {
                     super::BinaryOperation::Subtract => OpCode::Sub,
                     super::BinaryOperation::Multiply => OpCode::Mul,
                     super::BinaryOperation::Divide => OpCode::Div,
+                    super::BinaryOperation::IntDivide => OpCode::IntDiv,
+                    super::BinaryOperation::Remainder => OpCode::Rem,
+                    super::BinaryOperation::BitAnd => OpCode::BitAnd,
+                    super::BinaryOperation::BitOr => OpCode::BitOr,
+                    super::BinaryOperation::BitXor => OpCode::BitXor,
+                    super::BinaryOperation::ShiftLeft => OpCode::Shl,
+                    super::BinaryOperation::ShiftRight => OpCode::Shr,
                 })
             }
         }
+        compiler.locate(start, self.span());
     }
 }
@@ -0,0 +1,107 @@
+use crate::{
+    common::{opcode::OpCode, value::AsValue},
+    frontend::{
+        ast::{identifier::Identifier, CompileToBytecode},
+        compiler::Compiler,
+        span::Span,
+    },
+};
+
+use super::{AsExpr, Expression};
+
+/// `object.name`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    pub object: Box<Expression>,
+    pub name: Identifier,
+    pub span: Span,
+}
+/// `object.name = value`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetProperty {
+    pub object: Box<Expression>,
+    pub name: Identifier,
+    pub value: Box<Expression>,
+    pub span: Span,
+}
+/// `object.name(arguments)`, compiled without creating a bound method
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invoke {
+    pub object: Box<Expression>,
+    pub name: Identifier,
+    pub arguments: Vec<Expression>,
+    pub named: Vec<(Identifier, Expression)>,
+    pub span: Span,
+}
+impl Compiler<'_> {
+    fn property_name(&mut self, name: &Identifier) -> u16 {
+        self.bytecode
+            .function
+            .chunk
+            .emit_value(name.lexeme.clone().to_value())
+    }
+}
+impl CompileToBytecode for Property {
+    fn to_bytecode(&self, compiler: &mut Compiler) {
+        self.object.to_bytecode(compiler);
+        let name = compiler.property_name(&self.name);
+        compiler
+            .bytecode
+            .function
+            .chunk
+            .emit_op(OpCode::GetProperty(name));
+    }
+}
+impl CompileToBytecode for SetProperty {
+    fn to_bytecode(&self, compiler: &mut Compiler) {
+        self.object.to_bytecode(compiler);
+        self.value.to_bytecode(compiler);
+        let name = compiler.property_name(&self.name);
+        compiler
+            .bytecode
+            .function
+            .chunk
+            .emit_op(OpCode::SetProperty(name));
+    }
+}
+impl CompileToBytecode for Invoke {
+    fn to_bytecode(&self, compiler: &mut Compiler) {
+        self.object.to_bytecode(compiler);
+        let name = compiler.property_name(&self.name);
+        if !self.named.is_empty() {
+            // named arguments go through a plain call on the property
+            compiler
+                .bytecode
+                .function
+                .chunk
+                .emit_op(OpCode::GetProperty(name));
+        }
+        for argument in &self.arguments {
+            argument.to_bytecode(compiler);
+        }
+        if !self.named.is_empty() {
+            compiler.emit_call(self.arguments.len(), &self.named);
+            return;
+        }
+        compiler
+            .bytecode
+            .function
+            .chunk
+            .emit_op(OpCode::Invoke(name, self.arguments.len()));
+    }
+}
+impl AsExpr for Property {
+    fn to_expr(self) -> Expression {
+        Expression::Property(self)
+    }
+}
+impl AsExpr for SetProperty {
+    fn to_expr(self) -> Expression {
+        Expression::SetProperty(self)
+    }
+}
+impl AsExpr for Invoke {
+    fn to_expr(self) -> Expression {
+        Expression::Invoke(self)
+    }
+}
@@ -1,6 +1,10 @@
 use crate::{
     common::{opcode::OpCode, value::AsValue},
-    frontend::ast::{identifier::Identifier, CompileToBytecode},
+    frontend::{
+        ast::{identifier::Identifier, CompileToBytecode},
+        resolver::Resolution,
+        span::Span,
+    },
 };
 
 use super::Expression;
@@ -9,24 +13,27 @@ use super::Expression;
 pub struct VariableAssignment {
     pub initializer: Box<Expression>,
     pub name: Identifier,
+    pub span: Span,
 }
 impl VariableAssignment {
     pub fn to_bytecode(&self, compiler: &mut crate::frontend::compiler::Compiler) {
+        // only the outermost assignment of a statement gets to consume the value,
+        // `a = b = 1;` still needs `b = 1` to leave 1 on the stack
+        let consumes = std::mem::take(&mut compiler.bytecode.compiling_statement);
         self.initializer.to_bytecode(compiler);
-        let local = compiler.resolve_local(&self.name.value);
-        if let Some(local) = local {
-            if compiler.bytecode.compiling_statement {
+        if let Resolution::Local(local) = self.name.resolution {
+            if consumes {
                 compiler
                     .bytecode
                     .function
                     .chunk
-                    .emit_op(OpCode::SetLocalConsumes(local as u16));
+                    .emit_op(OpCode::SetLocalConsumes(local));
             } else {
                 compiler
                     .bytecode
                     .function
                     .chunk
-                    .emit_op(OpCode::SetLocal(local as u16));
+                    .emit_op(OpCode::SetLocal(local));
             }
             return;
         }
@@ -34,11 +41,14 @@ impl VariableAssignment {
             .bytecode
             .function
             .chunk
-            .emit_value(self.name.value.lexeme.to_value());
+            .emit_value(self.name.lexeme.to_value());
         compiler
             .bytecode
             .function
             .chunk
-            .emit_op(OpCode::SetGlobal(name))
+            .emit_op(OpCode::SetGlobal(name));
+        if consumes {
+            compiler.bytecode.function.chunk.emit_op(OpCode::Pop);
+        }
     }
 }
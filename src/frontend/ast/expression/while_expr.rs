@@ -1,4 +1,7 @@
-use crate::{common::opcode::OpCode, frontend::ast::CompileToBytecode};
+use crate::{
+    common::opcode::OpCode,
+    frontend::{ast::CompileToBytecode, span::Span},
+};
 
 use super::{block::Block, AsExpr, Expression};
 
@@ -6,6 +9,7 @@ use super::{block::Block, AsExpr, Expression};
 pub struct WhileExpr {
     pub predicate: Box<Expression>,
     pub block: Block,
+    pub span: Span,
 }
 impl AsExpr for WhileExpr {
     fn to_expr(self) -> Expression {
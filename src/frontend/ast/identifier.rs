@@ -1,9 +1,6 @@
 use crate::{
     common::{opcode::OpCode, value::AsValue},
-    frontend::{
-        compiler::Compiler,
-        scanner::{Token, TokenKind},
-    },
+    frontend::{compiler::Compiler, resolver::Resolution, scanner::Token},
 };
 
 use super::{
@@ -14,38 +11,39 @@ use super::{
 #[derive(Debug, Clone, PartialEq)]
 pub struct Identifier {
     pub value: Token,
+    /// the name as written, copied out of the source
+    pub lexeme: String,
+    /// filled in by the [resolver](crate::frontend::resolver)
+    pub resolution: Resolution,
 }
 
 impl CompileToBytecode for Identifier {
     fn to_bytecode(&self, compiler: &mut Compiler) {
-        let local = compiler.resolve_local(&self.value);
         let function = &mut compiler.bytecode.function;
-        #[allow(unused_assignments)]
-        let mut op: OpCode = OpCode::Nop;
-        if let Some(index) = local {
-            op = OpCode::GetLocal(index as u16);
-        } else {
-            let name = function.chunk.emit_value(self.value.lexeme.to_value());
-            op = OpCode::GetGlobal(name)
-        }
-
+        let op = match self.resolution {
+            Resolution::Local(slot) => OpCode::GetLocal(slot),
+            Resolution::Global => {
+                OpCode::GetGlobal(function.chunk.emit_value(self.lexeme.to_value()))
+            }
+            Resolution::Unresolved => unreachable!("`{}` wasn't resolved", self.lexeme),
+        };
         function.chunk.emit_op(op);
     }
 }
-impl<'a> Compiler<'a> {
-    pub fn resolve_local(&mut self, name: &Token) -> Option<usize> {
-        assert_eq!(name.kind, TokenKind::Identifier);
-        for (i, token) in self.bytecode.locals[0..self.bytecode.local_count]
-            .iter()
-            .enumerate()
-            .rev()
-        {
-            if name.lexeme.eq(&token.name.lexeme) {
-                return Some(i);
+impl Identifier {
+    /// declares the value on top of the stack as the variable this name was
+    /// resolved to
+    pub fn define(&self, compiler: &mut Compiler) {
+        match self.resolution {
+            // the value is already in the slot the resolver gave it
+            Resolution::Local(_) => {}
+            Resolution::Global => {
+                let chunk = &mut compiler.bytecode.function.chunk;
+                let name = chunk.emit_value(self.lexeme.to_value());
+                chunk.emit_op(OpCode::DefineGlobal(name));
             }
+            Resolution::Unresolved => unreachable!("`{}` wasn't resolved", self.lexeme),
         }
-
-        None
     }
 }
 impl AsNode for Identifier {
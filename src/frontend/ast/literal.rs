@@ -7,24 +7,18 @@ use crate::{
     frontend::compiler::Compiler,
 };
 
-use super::{
-    expression::{AsExpr, Expression},
-    node::Node,
-    CompileToBytecode,
-};
+use super::CompileToBytecode;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Literal {
     Number(f64),
+    Int(i64),
     String(String),
     Bool(bool),
     Void,
 }
 
 impl Literal {
-    pub fn as_node(self) -> Node {
-        Node::Literal(self)
-    }
     pub fn as_number(self) -> f64 {
         match self {
             Literal::Number(number) => number,
@@ -37,6 +31,7 @@ impl From<Literal> for Value {
         match literal {
             Literal::Void => Value::Void,
             Literal::Number(num) => Value::Number(num),
+            Literal::Int(int) => Value::Int(int),
             Literal::String(string) => {
                 let mut interner = STRING_INTERNER.lock().expect("already?");
                 Value::String(interner.get_or_intern(&string))
@@ -52,6 +47,7 @@ impl CompileToBytecode for Literal {
         let pos = match self {
             Literal::Void => function.chunk.emit_value(Value::Void),
             Literal::Number(number) => function.chunk.emit_value(Value::Number(*number)),
+            Literal::Int(int) => function.chunk.emit_value(Value::Int(*int)),
             Literal::String(string) => function.chunk.emit_value(string.to_value()),
             Literal::Bool(bool) => {
                 if *bool {
@@ -65,8 +61,3 @@ impl CompileToBytecode for Literal {
         function.chunk.emit_op(OpCode::Constant(pos))
     }
 }
-impl AsExpr for Literal {
-    fn to_expr(self) -> super::expression::Expression {
-        Expression::Literal(self)
-    }
-}
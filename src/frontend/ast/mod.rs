@@ -2,17 +2,29 @@ use self::expression::Expression;
 
 use super::compiler::Compiler;
 pub mod declaration;
+pub mod directive;
+pub mod dump;
 pub mod expression;
 pub mod identifier;
 pub mod literal;
 pub mod node;
 pub mod statement;
+pub mod visit;
+pub mod visit_mut;
 #[derive(Debug, PartialEq, Clone)]
 pub enum BinaryOperation {
     Add,
     Subtract,
     Multiply,
     Divide,
+    /// `~/`, rounds towards negative infinity
+    IntDivide,
+    Remainder,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
 }
 
 pub trait CompileToBytecode {
@@ -1,54 +1,42 @@
-use std::fmt::Debug;
-
-use crate::frontend::{compiler::Compiler, scanner::Position};
+use crate::frontend::{compiler::Compiler, span::Span};
 
 use super::{
-    declaration::Declaration,
-    expression::{AsExpr, Expression},
+    declaration::{
+        class::ClassDeclaration,
+        enum_declaration::{EnumDeclaration, EnumVariant},
+        function::FunctionDeclaration,
+        test::TestDeclaration,
+        variable_declaration::VariableDeclaration,
+        Declaration,
+    },
+    directive::Directive,
+    expression::{
+        block::Block,
+        call_expr::CallExpr,
+        comparison::Comparison,
+        function_expr::FunctionExpr,
+        if_expr::IfExpr,
+        match_expr::{MatchArm, MatchExpr},
+        property::{Invoke, Property, SetProperty},
+        variable_assignment::VariableAssignment,
+        while_expr::WhileExpr,
+        AsExpr, BinaryExpr, Expression,
+    },
     identifier::Identifier,
-    literal::Literal,
-    statement::Statement,
+    statement::{assertion::Assertion, return_stmt::ReturnStmt, try_catch::TryCatch, Statement},
     CompileToBytecode,
 };
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Node {
     Expression(Expression),
-    Literal(Literal),
     Statement(Statement),
     Declaration(Declaration),
     Identifier(Identifier),
+    Directive(Directive),
     None,
     /// use this one if you don't want the node to be emitted
     Empty,
-    Emit(fn(compiler: &mut Compiler) -> ()),
-}
-impl Debug for Node {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Expression(arg0) => f.debug_tuple("Expression").field(arg0).finish(),
-            Self::Literal(arg0) => f.debug_tuple("Literal").field(arg0).finish(),
-            Self::Statement(arg0) => f.debug_tuple("Statement").field(arg0).finish(),
-            Self::Declaration(arg0) => f.debug_tuple("Declaration").field(arg0).finish(),
-            Self::Identifier(arg0) => f.debug_tuple("Identifier").field(arg0).finish(),
-            Self::None => write!(f, "None"),
-            Self::Empty => write!(f, "Empty"),
-            Self::Emit(_arg0) => f.debug_tuple("Emit").finish(),
-        }
-    }
-}
-impl PartialEq for Node {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Self::Expression(l0), Self::Expression(r0)) => l0 == r0,
-            (Self::Literal(l0), Self::Literal(r0)) => l0 == r0,
-            (Self::Statement(l0), Self::Statement(r0)) => l0 == r0,
-            (Self::Declaration(l0), Self::Declaration(r0)) => l0 == r0,
-            (Self::Identifier(l0), Self::Identifier(r0)) => l0 == r0,
-            // (Self::Emit(l0), Self::Emit(r0)) => l0 == r0,
-            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
-        }
-    }
 }
 impl Node {
     pub fn as_identifier(self) -> Identifier {
@@ -58,25 +46,17 @@ impl Node {
 
         identifier
     }
-    pub fn as_literal(self) -> Literal {
-        let Node::Literal(literal) = self else {
-            panic!()
-        };
-
-        literal
-    }
 }
 impl AsExpr for Node {
     fn to_expr(self) -> Expression {
         match self {
             Node::Expression(expr) => expr,
-            Node::Literal(literal) => Expression::Literal(literal),
             Node::Declaration(_) => unimplemented!(),
             Node::Statement(_) => unimplemented!(),
             Node::Identifier(identifier) => Expression::Identifier(identifier),
             Node::None => panic!(),
             Node::Empty => panic!(),
-            Node::Emit(_) => panic!(),
+            Node::Directive(_) => panic!(),
         }
     }
 }
@@ -86,19 +66,125 @@ pub trait AsNode {
 impl CompileToBytecode for Node {
     // we need it to emit constants
     fn to_bytecode(&self, compiler: &mut Compiler) {
-        let _function = &mut compiler.bytecode.function;
+        let start = compiler.bytecode.function.chunk.code.len();
         match self {
             Node::Expression(expr) => expr.to_bytecode(compiler),
             Node::Statement(statement) => statement.to_bytecode(compiler),
             Node::Identifier(identifier) => identifier.to_bytecode(compiler),
-            Node::Literal(literal) => literal.to_bytecode(compiler),
             Node::Declaration(declaration) => declaration.to_bytecode(compiler),
-            Node::Emit(emit) => emit(compiler),
+            Node::Directive(directive) => directive.to_bytecode(compiler),
             _ => unimplemented!(),
         }
+        compiler.locate(start, self.span());
     }
 }
 
+/// where a node was written in the source, from its first token to its last
 pub trait AstPosition {
-    fn position(&self) -> &Position;
+    fn span(&self) -> Span;
+}
+macro_rules! span_field {
+    ($($node:ty),*) => {$(
+        impl AstPosition for $node {
+            fn span(&self) -> Span {
+                self.span
+            }
+        }
+    )*};
+}
+span_field!(
+    BinaryExpr,
+    Block,
+    CallExpr,
+    Comparison,
+    FunctionExpr,
+    IfExpr,
+    Invoke,
+    MatchArm,
+    MatchExpr,
+    Property,
+    SetProperty,
+    VariableAssignment,
+    WhileExpr,
+    Assertion,
+    ReturnStmt,
+    TryCatch,
+    ClassDeclaration,
+    EnumDeclaration,
+    FunctionDeclaration,
+    TestDeclaration,
+    VariableDeclaration,
+    Directive
+);
+impl AstPosition for EnumVariant {
+    fn span(&self) -> Span {
+        let last = self.fields.last().unwrap_or(&self.name);
+        self.name.span().to(last.span())
+    }
+}
+impl AstPosition for Identifier {
+    fn span(&self) -> Span {
+        self.value.span
+    }
+}
+impl AstPosition for Expression {
+    fn span(&self) -> Span {
+        match self {
+            Expression::Grouping(_, span)
+            | Expression::Literal(_, span)
+            | Expression::Not(_, span)
+            | Expression::Negate(_, span)
+            | Expression::Tuple(_, span)
+            | Expression::Array(_, span) => *span,
+            Expression::Binary(binary) => binary.span(),
+            Expression::VariableAssignment(assignment) => assignment.span(),
+            Expression::Block(block) => block.span(),
+            Expression::Identifier(identifier) => identifier.span(),
+            Expression::If(if_expr) => if_expr.span(),
+            Expression::Match(match_expr) => match_expr.span(),
+            Expression::While(while_expr) => while_expr.span(),
+            Expression::CallExpr(call) => call.span(),
+            Expression::Function(function) => function.span(),
+            Expression::Comparison(comparison) => comparison.span(),
+            Expression::Property(property) => property.span(),
+            Expression::SetProperty(property) => property.span(),
+            Expression::Invoke(invoke) => invoke.span(),
+            Expression::SelfRef(token) => token.span,
+        }
+    }
+}
+impl AstPosition for Statement {
+    fn span(&self) -> Span {
+        match self {
+            Statement::Expression(expr) => expr.span(),
+            Statement::Print(_, span) | Statement::Throw(_, span) => *span,
+            Statement::Assert(assertion) => assertion.span(),
+            Statement::Return(return_stmt) => return_stmt.span(),
+            Statement::Try(try_catch) => try_catch.span(),
+        }
+    }
+}
+impl AstPosition for Declaration {
+    fn span(&self) -> Span {
+        match self {
+            Declaration::VariableDeclaration(declaration) => declaration.span(),
+            Declaration::FunctionDeclaration(declaration) => declaration.span(),
+            Declaration::TestDeclaration(declaration) => declaration.span(),
+            Declaration::ClassDeclaration(declaration) => declaration.span(),
+            Declaration::EnumDeclaration(declaration) => declaration.span(),
+        }
+    }
+}
+impl AstPosition for Node {
+    /// empty for [`Node::None`] and [`Node::Empty`]
+    fn span(&self) -> Span {
+        match self {
+            Node::Expression(expr) => expr.span(),
+            Node::Statement(statement) => statement.span(),
+            Node::Declaration(declaration) => declaration.span(),
+            Node::Identifier(identifier) => identifier.span(),
+            Node::Directive(directive) => directive.span(),
+            Node::None | Node::Empty => Span::default(),
+        }
+    }
 }
@@ -0,0 +1,61 @@
+use crate::{
+    common::{chunk::AssertionInfo, opcode::OpCode},
+    frontend::{
+        ast::{expression::Expression, CompileToBytecode},
+        compiler::Compiler,
+        scanner::Token,
+        span::Span,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AssertionKind {
+    /// `assert_eq lhs, rhs`
+    Eq,
+    /// `assert_ne lhs, rhs`
+    Ne,
+    /// `assert condition`
+    True,
+}
+
+/// any of the assertion statements, they all take an optional message as
+/// their last argument
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assertion {
+    pub kind: AssertionKind,
+    pub lhs: Expression,
+    /// only `None` for [`AssertionKind::True`]
+    pub rhs: Option<Expression>,
+    pub message: Option<Expression>,
+    /// how the assertion was written, `fib(3) == 2` for `assert_eq fib(3), 2;`
+    pub source: String,
+    /// the `assert`/`assert_eq`/`assert_ne` keyword
+    pub token: Token,
+    pub span: Span,
+}
+
+impl CompileToBytecode for Assertion {
+    fn to_bytecode(&self, compiler: &mut Compiler) {
+        self.lhs.to_bytecode(compiler);
+        if let Some(rhs) = &self.rhs {
+            rhs.to_bytecode(compiler);
+        }
+        if let Some(message) = &self.message {
+            message.to_bytecode(compiler);
+        }
+
+        let position = compiler.diagnostics.borrow().position(self.token.span);
+        let chunk = &mut compiler.bytecode.function.chunk;
+        let info = chunk.add_assertion(AssertionInfo {
+            source: self.source.clone(),
+            line: position.line,
+            column: position.column,
+            has_message: self.message.is_some(),
+        });
+        chunk.emit_op(match self.kind {
+            AssertionKind::Eq => OpCode::AssertEq(info),
+            AssertionKind::Ne => OpCode::AssertNe(info),
+            AssertionKind::True => OpCode::Assert(info),
+        })
+    }
+}
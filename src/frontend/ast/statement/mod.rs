@@ -1,6 +1,11 @@
-use crate::{common::opcode::OpCode, frontend::compiler::Compiler};
+use crate::{
+    common::opcode::OpCode,
+    frontend::{compiler::Compiler, span::Span},
+};
+pub mod assertion;
 pub mod return_stmt;
-use self::return_stmt::ReturnStmt;
+pub mod try_catch;
+use self::{assertion::Assertion, return_stmt::ReturnStmt, try_catch::TryCatch};
 
 use super::{
     expression::{AsExpr, Expression},
@@ -10,10 +15,11 @@ use super::{
 #[derive(Debug, Clone, PartialEq)]
 pub enum Statement {
     Expression(Expression),
-    Print(Box<Node>),
-    AssertEq(Expression, Expression),
-    AssertNe(Expression, Expression),
+    Print(Box<Node>, Span),
+    Assert(Box<Assertion>),
     Return(ReturnStmt),
+    Try(Box<TryCatch>),
+    Throw(Expression, Span),
 }
 
 impl AsNode for Statement {
@@ -31,14 +37,15 @@ impl AsExpr for Statement {
 }
 impl CompileToBytecode for Statement {
     fn to_bytecode(&self, compiler: &mut Compiler) {
-        compiler.bytecode.compiling_statement = true;
         match self {
             Statement::Return(return_stmt) => return_stmt.to_bytecode(compiler),
             Statement::Expression(expr) => match &expr {
-                Expression::If(_)
-                | Expression::VariableAssignment(_)
-                | Expression::Block(_)
-                | Expression::While(_) => {
+                Expression::VariableAssignment(_) => {
+                    // the assignment takes its value off the stack itself
+                    compiler.bytecode.compiling_statement = true;
+                    expr.to_bytecode(compiler);
+                }
+                Expression::If(_) | Expression::Block(_) | Expression::While(_) => {
                     expr.to_bytecode(compiler);
                 }
 
@@ -47,24 +54,17 @@ impl CompileToBytecode for Statement {
                     compiler.bytecode.function.chunk.emit_op(OpCode::Pop)
                 }
             },
-            Statement::Print(expr) => {
+            Statement::Print(expr, _) => {
                 expr.to_bytecode(compiler);
                 compiler.bytecode.function.chunk.emit_op(OpCode::Print);
             }
-            Statement::AssertEq(lhs, rhs) => {
-                lhs.to_bytecode(compiler);
-                rhs.to_bytecode(compiler);
-
-                compiler.bytecode.function.chunk.emit_op(OpCode::AssertEq)
-            }
-            Statement::AssertNe(lhs, rhs) => {
-                lhs.to_bytecode(compiler);
-                rhs.to_bytecode(compiler);
-
-                compiler.bytecode.function.chunk.emit_op(OpCode::AssertNe)
+            Statement::Assert(assertion) => assertion.to_bytecode(compiler),
+            Statement::Try(try_catch) => try_catch.to_bytecode(compiler),
+            Statement::Throw(value, _) => {
+                value.to_bytecode(compiler);
+                compiler.bytecode.function.chunk.emit_op(OpCode::Throw);
             }
         }
-        compiler.bytecode.compiling_statement = false;
     }
 }
 
@@ -1,22 +1,30 @@
 use crate::{
     common::opcode::OpCode,
-    frontend::ast::{expression::Expression, literal::Literal, CompileToBytecode},
+    frontend::{
+        ast::{expression::Expression, literal::Literal, CompileToBytecode},
+        compiler::FunctionType,
+        span::Span,
+    },
 };
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ReturnStmt {
     pub expr: Option<Expression>,
+    pub span: Span,
 }
 
 impl CompileToBytecode for ReturnStmt {
     fn to_bytecode(&self, compiler: &mut crate::frontend::compiler::Compiler) {
-        self.expr
-            .as_ref()
-            .unwrap_or({
-                let this = Literal::Void;
-                &Expression::Literal(this)
-            })
-            .to_bytecode(compiler);
+        if compiler.bytecode.function_type == FunctionType::Initializer {
+            // initializers always hand back the instance
+            compiler.bytecode.function.chunk.emit_op(OpCode::GetSelf);
+            compiler.bytecode.function.chunk.emit_op(OpCode::Return);
+            return;
+        }
+        match &self.expr {
+            Some(expr) => expr.to_bytecode(compiler),
+            None => Literal::Void.to_bytecode(compiler),
+        }
         compiler.bytecode.function.chunk.emit_op(OpCode::Return);
     }
 }
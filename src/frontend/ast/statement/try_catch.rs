@@ -0,0 +1,47 @@
+use crate::{
+    common::opcode::OpCode,
+    frontend::{
+        ast::{expression::block::Block, identifier::Identifier, CompileToBytecode},
+        compiler::Compiler,
+        span::Span,
+    },
+};
+
+/// `try { ... } catch (error) { ... }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TryCatch {
+    pub body: Block,
+    /// the name the caught error is bound to
+    pub binding: Identifier,
+    pub handler: Block,
+    pub span: Span,
+}
+
+impl CompileToBytecode for TryCatch {
+    fn to_bytecode(&self, compiler: &mut Compiler) {
+        let push_handler = compiler.bytecode.function.chunk.code.len();
+        compiler
+            .bytecode
+            .function
+            .chunk
+            .emit_op(OpCode::PushHandler(0xfff));
+        self.body.to_bytecode(compiler);
+        compiler.bytecode.function.chunk.emit_op(OpCode::PopHandler);
+        let skip_catch = compiler.bytecode.function.chunk.code.len();
+        compiler
+            .bytecode
+            .function
+            .chunk
+            .emit_op(OpCode::JumpTo(0xfff));
+
+        // the vm jumps here with the error on top of the stack, which makes it
+        // the binding's local
+        let catch_start = compiler.bytecode.function.chunk.code.len();
+        compiler.bytecode.function.chunk.code[push_handler] = OpCode::PushHandler(catch_start);
+        self.handler.to_bytecode(compiler);
+        compiler.pop_locals(1);
+
+        let after_catch = compiler.bytecode.function.chunk.code.len();
+        compiler.bytecode.function.chunk.code[skip_catch] = OpCode::JumpTo(after_catch);
+    }
+}
@@ -0,0 +1,360 @@
+//! walks the ast by reference, implement [`Visitor`], override the methods for
+//! the nodes you care about and call the matching `walk_*` function from them
+//! to keep going into their children
+
+use super::{
+    declaration::{
+        class::ClassDeclaration,
+        enum_declaration::EnumDeclaration,
+        function::{FunctionDeclaration, Parameter},
+        test::TestDeclaration,
+        variable_declaration::{Binding, VariableDeclaration},
+        Declaration,
+    },
+    directive::Directive,
+    expression::{
+        block::Block,
+        call_expr::CallExpr,
+        comparison::Comparison,
+        function_expr::FunctionExpr,
+        if_expr::IfExpr,
+        match_expr::{MatchArm, MatchExpr, Pattern},
+        property::{Invoke, Property, SetProperty},
+        variable_assignment::VariableAssignment,
+        while_expr::WhileExpr,
+        BinaryExpr, Expression,
+    },
+    identifier::Identifier,
+    literal::Literal,
+    node::Node,
+    statement::{assertion::Assertion, return_stmt::ReturnStmt, try_catch::TryCatch, Statement},
+};
+
+/// every method defaults to walking the node's children, so a visitor only
+/// has to override what it looks at
+pub trait Visitor {
+    fn visit_node(&mut self, node: &Node) {
+        walk_node(self, node)
+    }
+    fn visit_expression(&mut self, expr: &Expression) {
+        walk_expression(self, expr)
+    }
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement)
+    }
+    fn visit_declaration(&mut self, declaration: &Declaration) {
+        walk_declaration(self, declaration)
+    }
+    /// the names of variables, functions, classes, enums and parameters, both
+    /// where they're declared and where they're used, property names and the
+    /// labels of named arguments aren't visited
+    fn visit_identifier(&mut self, _identifier: &Identifier) {}
+    fn visit_literal(&mut self, _literal: &Literal) {}
+    fn visit_directive(&mut self, directive: &Directive) {
+        walk_directive(self, directive)
+    }
+    fn visit_block(&mut self, block: &Block) {
+        walk_block(self, block)
+    }
+    fn visit_binary(&mut self, binary: &BinaryExpr) {
+        walk_binary(self, binary)
+    }
+    fn visit_variable_assignment(&mut self, assignment: &VariableAssignment) {
+        walk_variable_assignment(self, assignment)
+    }
+    fn visit_if(&mut self, if_expr: &IfExpr) {
+        walk_if(self, if_expr)
+    }
+    fn visit_while(&mut self, while_expr: &WhileExpr) {
+        walk_while(self, while_expr)
+    }
+    fn visit_match(&mut self, match_expr: &MatchExpr) {
+        walk_match(self, match_expr)
+    }
+    fn visit_match_arm(&mut self, arm: &MatchArm) {
+        walk_match_arm(self, arm)
+    }
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        walk_pattern(self, pattern)
+    }
+    fn visit_call(&mut self, call: &CallExpr) {
+        walk_call(self, call)
+    }
+    fn visit_function(&mut self, function: &FunctionExpr) {
+        walk_function(self, function)
+    }
+    fn visit_parameter(&mut self, parameter: &Parameter) {
+        walk_parameter(self, parameter)
+    }
+    fn visit_comparison(&mut self, comparison: &Comparison) {
+        walk_comparison(self, comparison)
+    }
+    fn visit_property(&mut self, property: &Property) {
+        walk_property(self, property)
+    }
+    fn visit_set_property(&mut self, property: &SetProperty) {
+        walk_set_property(self, property)
+    }
+    fn visit_invoke(&mut self, invoke: &Invoke) {
+        walk_invoke(self, invoke)
+    }
+    fn visit_assertion(&mut self, assertion: &Assertion) {
+        walk_assertion(self, assertion)
+    }
+    fn visit_return(&mut self, return_stmt: &ReturnStmt) {
+        walk_return(self, return_stmt)
+    }
+    fn visit_try_catch(&mut self, try_catch: &TryCatch) {
+        walk_try_catch(self, try_catch)
+    }
+    fn visit_variable_declaration(&mut self, declaration: &VariableDeclaration) {
+        walk_variable_declaration(self, declaration)
+    }
+    fn visit_binding(&mut self, binding: &Binding) {
+        walk_binding(self, binding)
+    }
+    fn visit_function_declaration(&mut self, declaration: &FunctionDeclaration) {
+        walk_function_declaration(self, declaration)
+    }
+    fn visit_test_declaration(&mut self, declaration: &TestDeclaration) {
+        walk_test_declaration(self, declaration)
+    }
+    fn visit_class_declaration(&mut self, declaration: &ClassDeclaration) {
+        walk_class_declaration(self, declaration)
+    }
+    fn visit_enum_declaration(&mut self, declaration: &EnumDeclaration) {
+        walk_enum_declaration(self, declaration)
+    }
+}
+
+pub fn walk_node<V: Visitor + ?Sized>(visitor: &mut V, node: &Node) {
+    match node {
+        Node::Expression(expr) => visitor.visit_expression(expr),
+        Node::Statement(statement) => visitor.visit_statement(statement),
+        Node::Declaration(declaration) => visitor.visit_declaration(declaration),
+        Node::Identifier(identifier) => visitor.visit_identifier(identifier),
+        Node::Directive(directive) => visitor.visit_directive(directive),
+        Node::None | Node::Empty => {}
+    }
+}
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expression) {
+    match expr {
+        Expression::Grouping(inner, _)
+        | Expression::Not(inner, _)
+        | Expression::Negate(inner, _) => visitor.visit_expression(inner),
+        Expression::Binary(binary) => visitor.visit_binary(binary),
+        Expression::Literal(literal, _) => visitor.visit_literal(literal),
+        Expression::VariableAssignment(assignment) => visitor.visit_variable_assignment(assignment),
+        Expression::Block(block) => visitor.visit_block(block),
+        Expression::Identifier(identifier) => visitor.visit_identifier(identifier),
+        Expression::If(if_expr) => visitor.visit_if(if_expr),
+        Expression::Match(match_expr) => visitor.visit_match(match_expr),
+        Expression::While(while_expr) => visitor.visit_while(while_expr),
+        Expression::CallExpr(call) => visitor.visit_call(call),
+        Expression::Function(function) => visitor.visit_function(function),
+        Expression::Comparison(comparison) => visitor.visit_comparison(comparison),
+        Expression::Tuple(elements, _) | Expression::Array(elements, _) => {
+            for element in elements {
+                visitor.visit_expression(element);
+            }
+        }
+        Expression::Property(property) => visitor.visit_property(property),
+        Expression::SetProperty(property) => visitor.visit_set_property(property),
+        Expression::Invoke(invoke) => visitor.visit_invoke(invoke),
+        Expression::SelfRef(_) => {}
+    }
+}
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &Statement) {
+    match statement {
+        Statement::Expression(expr) | Statement::Throw(expr, _) => visitor.visit_expression(expr),
+        Statement::Print(node, _) => visitor.visit_node(node),
+        Statement::Assert(assertion) => visitor.visit_assertion(assertion),
+        Statement::Return(return_stmt) => visitor.visit_return(return_stmt),
+        Statement::Try(try_catch) => visitor.visit_try_catch(try_catch),
+    }
+}
+pub fn walk_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &Declaration) {
+    match declaration {
+        Declaration::VariableDeclaration(declaration) => {
+            visitor.visit_variable_declaration(declaration)
+        }
+        Declaration::FunctionDeclaration(declaration) => {
+            visitor.visit_function_declaration(declaration)
+        }
+        Declaration::TestDeclaration(declaration) => visitor.visit_test_declaration(declaration),
+        Declaration::ClassDeclaration(declaration) => visitor.visit_class_declaration(declaration),
+        Declaration::EnumDeclaration(declaration) => visitor.visit_enum_declaration(declaration),
+    }
+}
+pub fn walk_directive<V: Visitor + ?Sized>(visitor: &mut V, directive: &Directive) {
+    for argument in &directive.arguments {
+        visitor.visit_expression(argument);
+    }
+}
+pub fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, block: &Block) {
+    for node in &block.declarations {
+        visitor.visit_node(node);
+    }
+}
+pub fn walk_binary<V: Visitor + ?Sized>(visitor: &mut V, binary: &BinaryExpr) {
+    visitor.visit_node(&binary.lhs);
+    visitor.visit_node(&binary.rhs);
+}
+pub fn walk_variable_assignment<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    assignment: &VariableAssignment,
+) {
+    visitor.visit_identifier(&assignment.name);
+    visitor.visit_expression(&assignment.initializer);
+}
+pub fn walk_if<V: Visitor + ?Sized>(visitor: &mut V, if_expr: &IfExpr) {
+    visitor.visit_expression(&if_expr.predicate);
+    visitor.visit_block(&if_expr.then);
+    if let Some(else_block) = &if_expr.else_block {
+        visitor.visit_block(else_block);
+    }
+}
+pub fn walk_while<V: Visitor + ?Sized>(visitor: &mut V, while_expr: &WhileExpr) {
+    visitor.visit_expression(&while_expr.predicate);
+    visitor.visit_block(&while_expr.block);
+}
+pub fn walk_match<V: Visitor + ?Sized>(visitor: &mut V, match_expr: &MatchExpr) {
+    visitor.visit_expression(&match_expr.value);
+    for arm in &match_expr.arms {
+        visitor.visit_match_arm(arm);
+    }
+}
+pub fn walk_match_arm<V: Visitor + ?Sized>(visitor: &mut V, arm: &MatchArm) {
+    visitor.visit_pattern(&arm.pattern);
+    if let Some(guard) = &arm.guard {
+        visitor.visit_expression(guard);
+    }
+    visitor.visit_expression(&arm.body);
+}
+pub fn walk_pattern<V: Visitor + ?Sized>(visitor: &mut V, pattern: &Pattern) {
+    match pattern {
+        Pattern::Literal(literal, _) => visitor.visit_literal(literal),
+        Pattern::Alternatives(alternatives) => {
+            for alternative in alternatives {
+                visitor.visit_pattern(alternative);
+            }
+        }
+        Pattern::Range { start, end, .. } => {
+            visitor.visit_literal(start);
+            visitor.visit_literal(end);
+        }
+        Pattern::Wildcard => {}
+        Pattern::Binding(identifier) => visitor.visit_identifier(identifier),
+    }
+}
+pub fn walk_call<V: Visitor + ?Sized>(visitor: &mut V, call: &CallExpr) {
+    visitor.visit_expression(&call.callee);
+    for argument in call.parameters.iter() {
+        visitor.visit_expression(argument);
+    }
+    for (_, argument) in &call.named {
+        visitor.visit_expression(argument);
+    }
+}
+pub fn walk_function<V: Visitor + ?Sized>(visitor: &mut V, function: &FunctionExpr) {
+    for parameter in &function.parameters {
+        visitor.visit_parameter(parameter);
+    }
+    visitor.visit_block(&function.block);
+}
+pub fn walk_parameter<V: Visitor + ?Sized>(visitor: &mut V, parameter: &Parameter) {
+    visitor.visit_identifier(&parameter.name);
+    if let Some(default) = &parameter.default {
+        visitor.visit_expression(default);
+    }
+}
+pub fn walk_comparison<V: Visitor + ?Sized>(visitor: &mut V, comparison: &Comparison) {
+    visitor.visit_expression(&comparison.lhs);
+    visitor.visit_expression(&comparison.rhs);
+}
+pub fn walk_property<V: Visitor + ?Sized>(visitor: &mut V, property: &Property) {
+    visitor.visit_expression(&property.object);
+}
+pub fn walk_set_property<V: Visitor + ?Sized>(visitor: &mut V, property: &SetProperty) {
+    visitor.visit_expression(&property.object);
+    visitor.visit_expression(&property.value);
+}
+pub fn walk_invoke<V: Visitor + ?Sized>(visitor: &mut V, invoke: &Invoke) {
+    visitor.visit_expression(&invoke.object);
+    for argument in &invoke.arguments {
+        visitor.visit_expression(argument);
+    }
+    for (_, argument) in &invoke.named {
+        visitor.visit_expression(argument);
+    }
+}
+pub fn walk_assertion<V: Visitor + ?Sized>(visitor: &mut V, assertion: &Assertion) {
+    visitor.visit_expression(&assertion.lhs);
+    if let Some(rhs) = &assertion.rhs {
+        visitor.visit_expression(rhs);
+    }
+    if let Some(message) = &assertion.message {
+        visitor.visit_expression(message);
+    }
+}
+pub fn walk_return<V: Visitor + ?Sized>(visitor: &mut V, return_stmt: &ReturnStmt) {
+    if let Some(expr) = &return_stmt.expr {
+        visitor.visit_expression(expr);
+    }
+}
+pub fn walk_try_catch<V: Visitor + ?Sized>(visitor: &mut V, try_catch: &TryCatch) {
+    visitor.visit_block(&try_catch.body);
+    visitor.visit_identifier(&try_catch.binding);
+    visitor.visit_block(&try_catch.handler);
+}
+pub fn walk_variable_declaration<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    declaration: &VariableDeclaration,
+) {
+    // the initializer runs before the names exist
+    visitor.visit_expression(&declaration.intializer);
+    visitor.visit_binding(&declaration.binding);
+}
+pub fn walk_binding<V: Visitor + ?Sized>(visitor: &mut V, binding: &Binding) {
+    match binding {
+        Binding::Name(name) => visitor.visit_identifier(name),
+        Binding::Tuple(names) => {
+            for name in names {
+                visitor.visit_identifier(name);
+            }
+        }
+        Binding::Array { names, rest } => {
+            for name in names {
+                visitor.visit_identifier(name);
+            }
+            if let Some(rest) = rest {
+                visitor.visit_identifier(rest);
+            }
+        }
+    }
+}
+pub fn walk_function_declaration<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    declaration: &FunctionDeclaration,
+) {
+    visitor.visit_identifier(&declaration.name);
+    for parameter in &declaration.parameters {
+        visitor.visit_parameter(parameter);
+    }
+    visitor.visit_block(&declaration.block);
+}
+pub fn walk_test_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &TestDeclaration) {
+    visitor.visit_block(&declaration.block);
+}
+pub fn walk_class_declaration<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    declaration: &ClassDeclaration,
+) {
+    visitor.visit_identifier(&declaration.name);
+    for method in &declaration.methods {
+        visitor.visit_function_declaration(method);
+    }
+}
+pub fn walk_enum_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &EnumDeclaration) {
+    visitor.visit_identifier(&declaration.name);
+}
@@ -0,0 +1,366 @@
+//! like [`super::visit`], but every node is handed over as `&mut` so a pass
+//! can rewrite the tree in place, implement [`VisitorMut`] and call the
+//! matching `walk_*` function to keep going into the children
+
+use super::{
+    declaration::{
+        class::ClassDeclaration,
+        enum_declaration::EnumDeclaration,
+        function::{FunctionDeclaration, Parameter},
+        test::TestDeclaration,
+        variable_declaration::{Binding, VariableDeclaration},
+        Declaration,
+    },
+    directive::Directive,
+    expression::{
+        block::Block,
+        call_expr::CallExpr,
+        comparison::Comparison,
+        function_expr::FunctionExpr,
+        if_expr::IfExpr,
+        match_expr::{MatchArm, MatchExpr, Pattern},
+        property::{Invoke, Property, SetProperty},
+        variable_assignment::VariableAssignment,
+        while_expr::WhileExpr,
+        BinaryExpr, Expression,
+    },
+    identifier::Identifier,
+    literal::Literal,
+    node::Node,
+    statement::{assertion::Assertion, return_stmt::ReturnStmt, try_catch::TryCatch, Statement},
+};
+
+/// every method defaults to walking the node's children, so a pass only has
+/// to override the nodes it rewrites
+pub trait VisitorMut {
+    fn visit_node(&mut self, node: &mut Node) {
+        walk_node(self, node)
+    }
+    fn visit_expression(&mut self, expr: &mut Expression) {
+        walk_expression(self, expr)
+    }
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        walk_statement(self, statement)
+    }
+    fn visit_declaration(&mut self, declaration: &mut Declaration) {
+        walk_declaration(self, declaration)
+    }
+    /// the names of variables, functions, classes, enums and parameters, both
+    /// where they're declared and where they're used, property names and the
+    /// labels of named arguments aren't visited
+    fn visit_identifier(&mut self, _identifier: &mut Identifier) {}
+    fn visit_literal(&mut self, _literal: &mut Literal) {}
+    fn visit_directive(&mut self, directive: &mut Directive) {
+        walk_directive(self, directive)
+    }
+    fn visit_block(&mut self, block: &mut Block) {
+        walk_block(self, block)
+    }
+    fn visit_binary(&mut self, binary: &mut BinaryExpr) {
+        walk_binary(self, binary)
+    }
+    fn visit_variable_assignment(&mut self, assignment: &mut VariableAssignment) {
+        walk_variable_assignment(self, assignment)
+    }
+    fn visit_if(&mut self, if_expr: &mut IfExpr) {
+        walk_if(self, if_expr)
+    }
+    fn visit_while(&mut self, while_expr: &mut WhileExpr) {
+        walk_while(self, while_expr)
+    }
+    fn visit_match(&mut self, match_expr: &mut MatchExpr) {
+        walk_match(self, match_expr)
+    }
+    fn visit_match_arm(&mut self, arm: &mut MatchArm) {
+        walk_match_arm(self, arm)
+    }
+    fn visit_pattern(&mut self, pattern: &mut Pattern) {
+        walk_pattern(self, pattern)
+    }
+    fn visit_call(&mut self, call: &mut CallExpr) {
+        walk_call(self, call)
+    }
+    fn visit_function(&mut self, function: &mut FunctionExpr) {
+        walk_function(self, function)
+    }
+    fn visit_parameter(&mut self, parameter: &mut Parameter) {
+        walk_parameter(self, parameter)
+    }
+    fn visit_comparison(&mut self, comparison: &mut Comparison) {
+        walk_comparison(self, comparison)
+    }
+    fn visit_property(&mut self, property: &mut Property) {
+        walk_property(self, property)
+    }
+    fn visit_set_property(&mut self, property: &mut SetProperty) {
+        walk_set_property(self, property)
+    }
+    fn visit_invoke(&mut self, invoke: &mut Invoke) {
+        walk_invoke(self, invoke)
+    }
+    fn visit_assertion(&mut self, assertion: &mut Assertion) {
+        walk_assertion(self, assertion)
+    }
+    fn visit_return(&mut self, return_stmt: &mut ReturnStmt) {
+        walk_return(self, return_stmt)
+    }
+    fn visit_try_catch(&mut self, try_catch: &mut TryCatch) {
+        walk_try_catch(self, try_catch)
+    }
+    fn visit_variable_declaration(&mut self, declaration: &mut VariableDeclaration) {
+        walk_variable_declaration(self, declaration)
+    }
+    fn visit_binding(&mut self, binding: &mut Binding) {
+        walk_binding(self, binding)
+    }
+    fn visit_function_declaration(&mut self, declaration: &mut FunctionDeclaration) {
+        walk_function_declaration(self, declaration)
+    }
+    fn visit_test_declaration(&mut self, declaration: &mut TestDeclaration) {
+        walk_test_declaration(self, declaration)
+    }
+    fn visit_class_declaration(&mut self, declaration: &mut ClassDeclaration) {
+        walk_class_declaration(self, declaration)
+    }
+    fn visit_enum_declaration(&mut self, declaration: &mut EnumDeclaration) {
+        walk_enum_declaration(self, declaration)
+    }
+}
+
+pub fn walk_node<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut Node) {
+    match node {
+        Node::Expression(expr) => visitor.visit_expression(expr),
+        Node::Statement(statement) => visitor.visit_statement(statement),
+        Node::Declaration(declaration) => visitor.visit_declaration(declaration),
+        Node::Identifier(identifier) => visitor.visit_identifier(identifier),
+        Node::Directive(directive) => visitor.visit_directive(directive),
+        Node::None | Node::Empty => {}
+    }
+}
+pub fn walk_expression<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expression) {
+    match expr {
+        Expression::Grouping(inner, _)
+        | Expression::Not(inner, _)
+        | Expression::Negate(inner, _) => visitor.visit_expression(inner),
+        Expression::Binary(binary) => visitor.visit_binary(binary),
+        Expression::Literal(literal, _) => visitor.visit_literal(literal),
+        Expression::VariableAssignment(assignment) => visitor.visit_variable_assignment(assignment),
+        Expression::Block(block) => visitor.visit_block(block),
+        Expression::Identifier(identifier) => visitor.visit_identifier(identifier),
+        Expression::If(if_expr) => visitor.visit_if(if_expr),
+        Expression::Match(match_expr) => visitor.visit_match(match_expr),
+        Expression::While(while_expr) => visitor.visit_while(while_expr),
+        Expression::CallExpr(call) => visitor.visit_call(call),
+        Expression::Function(function) => visitor.visit_function(function),
+        Expression::Comparison(comparison) => visitor.visit_comparison(comparison),
+        Expression::Tuple(elements, _) | Expression::Array(elements, _) => {
+            for element in elements {
+                visitor.visit_expression(element);
+            }
+        }
+        Expression::Property(property) => visitor.visit_property(property),
+        Expression::SetProperty(property) => visitor.visit_set_property(property),
+        Expression::Invoke(invoke) => visitor.visit_invoke(invoke),
+        Expression::SelfRef(_) => {}
+    }
+}
+pub fn walk_statement<V: VisitorMut + ?Sized>(visitor: &mut V, statement: &mut Statement) {
+    match statement {
+        Statement::Expression(expr) | Statement::Throw(expr, _) => visitor.visit_expression(expr),
+        Statement::Print(node, _) => visitor.visit_node(node),
+        Statement::Assert(assertion) => visitor.visit_assertion(assertion),
+        Statement::Return(return_stmt) => visitor.visit_return(return_stmt),
+        Statement::Try(try_catch) => visitor.visit_try_catch(try_catch),
+    }
+}
+pub fn walk_declaration<V: VisitorMut + ?Sized>(visitor: &mut V, declaration: &mut Declaration) {
+    match declaration {
+        Declaration::VariableDeclaration(declaration) => {
+            visitor.visit_variable_declaration(declaration)
+        }
+        Declaration::FunctionDeclaration(declaration) => {
+            visitor.visit_function_declaration(declaration)
+        }
+        Declaration::TestDeclaration(declaration) => visitor.visit_test_declaration(declaration),
+        Declaration::ClassDeclaration(declaration) => visitor.visit_class_declaration(declaration),
+        Declaration::EnumDeclaration(declaration) => visitor.visit_enum_declaration(declaration),
+    }
+}
+pub fn walk_directive<V: VisitorMut + ?Sized>(visitor: &mut V, directive: &mut Directive) {
+    for argument in &mut directive.arguments {
+        visitor.visit_expression(argument);
+    }
+}
+pub fn walk_block<V: VisitorMut + ?Sized>(visitor: &mut V, block: &mut Block) {
+    for node in &mut block.declarations {
+        visitor.visit_node(node);
+    }
+}
+pub fn walk_binary<V: VisitorMut + ?Sized>(visitor: &mut V, binary: &mut BinaryExpr) {
+    visitor.visit_node(&mut binary.lhs);
+    visitor.visit_node(&mut binary.rhs);
+}
+pub fn walk_variable_assignment<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    assignment: &mut VariableAssignment,
+) {
+    visitor.visit_identifier(&mut assignment.name);
+    visitor.visit_expression(&mut assignment.initializer);
+}
+pub fn walk_if<V: VisitorMut + ?Sized>(visitor: &mut V, if_expr: &mut IfExpr) {
+    visitor.visit_expression(&mut if_expr.predicate);
+    visitor.visit_block(&mut if_expr.then);
+    if let Some(else_block) = &mut if_expr.else_block {
+        visitor.visit_block(else_block);
+    }
+}
+pub fn walk_while<V: VisitorMut + ?Sized>(visitor: &mut V, while_expr: &mut WhileExpr) {
+    visitor.visit_expression(&mut while_expr.predicate);
+    visitor.visit_block(&mut while_expr.block);
+}
+pub fn walk_match<V: VisitorMut + ?Sized>(visitor: &mut V, match_expr: &mut MatchExpr) {
+    visitor.visit_expression(&mut match_expr.value);
+    for arm in &mut match_expr.arms {
+        visitor.visit_match_arm(arm);
+    }
+}
+pub fn walk_match_arm<V: VisitorMut + ?Sized>(visitor: &mut V, arm: &mut MatchArm) {
+    visitor.visit_pattern(&mut arm.pattern);
+    if let Some(guard) = &mut arm.guard {
+        visitor.visit_expression(guard);
+    }
+    visitor.visit_expression(&mut arm.body);
+}
+pub fn walk_pattern<V: VisitorMut + ?Sized>(visitor: &mut V, pattern: &mut Pattern) {
+    match pattern {
+        Pattern::Literal(literal, _) => visitor.visit_literal(literal),
+        Pattern::Alternatives(alternatives) => {
+            for alternative in alternatives {
+                visitor.visit_pattern(alternative);
+            }
+        }
+        Pattern::Range { start, end, .. } => {
+            visitor.visit_literal(start);
+            visitor.visit_literal(end);
+        }
+        Pattern::Wildcard => {}
+        Pattern::Binding(identifier) => visitor.visit_identifier(identifier),
+    }
+}
+pub fn walk_call<V: VisitorMut + ?Sized>(visitor: &mut V, call: &mut CallExpr) {
+    visitor.visit_expression(&mut call.callee);
+    for argument in call.parameters.iter_mut() {
+        visitor.visit_expression(argument);
+    }
+    for (_, argument) in &mut call.named {
+        visitor.visit_expression(argument);
+    }
+}
+pub fn walk_function<V: VisitorMut + ?Sized>(visitor: &mut V, function: &mut FunctionExpr) {
+    for parameter in &mut function.parameters {
+        visitor.visit_parameter(parameter);
+    }
+    visitor.visit_block(&mut function.block);
+}
+pub fn walk_parameter<V: VisitorMut + ?Sized>(visitor: &mut V, parameter: &mut Parameter) {
+    visitor.visit_identifier(&mut parameter.name);
+    if let Some(default) = &mut parameter.default {
+        visitor.visit_expression(default);
+    }
+}
+pub fn walk_comparison<V: VisitorMut + ?Sized>(visitor: &mut V, comparison: &mut Comparison) {
+    visitor.visit_expression(&mut comparison.lhs);
+    visitor.visit_expression(&mut comparison.rhs);
+}
+pub fn walk_property<V: VisitorMut + ?Sized>(visitor: &mut V, property: &mut Property) {
+    visitor.visit_expression(&mut property.object);
+}
+pub fn walk_set_property<V: VisitorMut + ?Sized>(visitor: &mut V, property: &mut SetProperty) {
+    visitor.visit_expression(&mut property.object);
+    visitor.visit_expression(&mut property.value);
+}
+pub fn walk_invoke<V: VisitorMut + ?Sized>(visitor: &mut V, invoke: &mut Invoke) {
+    visitor.visit_expression(&mut invoke.object);
+    for argument in &mut invoke.arguments {
+        visitor.visit_expression(argument);
+    }
+    for (_, argument) in &mut invoke.named {
+        visitor.visit_expression(argument);
+    }
+}
+pub fn walk_assertion<V: VisitorMut + ?Sized>(visitor: &mut V, assertion: &mut Assertion) {
+    visitor.visit_expression(&mut assertion.lhs);
+    if let Some(rhs) = &mut assertion.rhs {
+        visitor.visit_expression(rhs);
+    }
+    if let Some(message) = &mut assertion.message {
+        visitor.visit_expression(message);
+    }
+}
+pub fn walk_return<V: VisitorMut + ?Sized>(visitor: &mut V, return_stmt: &mut ReturnStmt) {
+    if let Some(expr) = &mut return_stmt.expr {
+        visitor.visit_expression(expr);
+    }
+}
+pub fn walk_try_catch<V: VisitorMut + ?Sized>(visitor: &mut V, try_catch: &mut TryCatch) {
+    visitor.visit_block(&mut try_catch.body);
+    visitor.visit_identifier(&mut try_catch.binding);
+    visitor.visit_block(&mut try_catch.handler);
+}
+pub fn walk_variable_declaration<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    declaration: &mut VariableDeclaration,
+) {
+    // the initializer runs before the names exist
+    visitor.visit_expression(&mut declaration.intializer);
+    visitor.visit_binding(&mut declaration.binding);
+}
+pub fn walk_binding<V: VisitorMut + ?Sized>(visitor: &mut V, binding: &mut Binding) {
+    match binding {
+        Binding::Name(name) => visitor.visit_identifier(name),
+        Binding::Tuple(names) => {
+            for name in names {
+                visitor.visit_identifier(name);
+            }
+        }
+        Binding::Array { names, rest } => {
+            for name in names {
+                visitor.visit_identifier(name);
+            }
+            if let Some(rest) = rest {
+                visitor.visit_identifier(rest);
+            }
+        }
+    }
+}
+pub fn walk_function_declaration<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    declaration: &mut FunctionDeclaration,
+) {
+    visitor.visit_identifier(&mut declaration.name);
+    for parameter in &mut declaration.parameters {
+        visitor.visit_parameter(parameter);
+    }
+    visitor.visit_block(&mut declaration.block);
+}
+pub fn walk_test_declaration<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    declaration: &mut TestDeclaration,
+) {
+    visitor.visit_block(&mut declaration.block);
+}
+pub fn walk_class_declaration<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    declaration: &mut ClassDeclaration,
+) {
+    visitor.visit_identifier(&mut declaration.name);
+    for method in &mut declaration.methods {
+        visitor.visit_function_declaration(method);
+    }
+}
+pub fn walk_enum_declaration<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    declaration: &mut EnumDeclaration,
+) {
+    visitor.visit_identifier(&mut declaration.name);
+}
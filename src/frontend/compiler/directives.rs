@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use crate::{
+    common::{
+        opcode::OpCode,
+        value::{rcrf, Value},
+    },
+    frontend::ast::directive::Directive,
+};
+
+use super::Compiler;
+
+/// what a directive expects after its name
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arguments {
+    /// just `#name`
+    None,
+    /// `#name [a, b, ...]` with any expressions
+    List,
+    /// `#name [a, b, ...]` where every expression has to be known at compile time
+    Constants,
+}
+
+#[derive(Clone)]
+pub struct DirectiveDefinition {
+    pub arguments: Arguments,
+    pub emit: fn(directive: &Directive, compiler: &mut Compiler),
+}
+
+impl std::fmt::Debug for DirectiveDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DirectiveDefinition")
+            .field("arguments", &self.arguments)
+            .finish()
+    }
+}
+
+/// every `#name` the parser accepts, the host can add its own with
+/// [`Directives::register`] before compiling
+#[derive(Debug, Clone, Default)]
+pub struct Directives {
+    definitions: HashMap<String, DirectiveDefinition>,
+}
+
+impl Directives {
+    pub fn builtin() -> Directives {
+        let mut directives = Directives::default();
+        directives.register("void", Arguments::None, |_, compiler| {
+            compiler.bytecode.function.chunk.emit_op(OpCode::Void)
+        });
+        directives.register("debug_stack", Arguments::None, |_, compiler| {
+            compiler
+                .bytecode
+                .function
+                .chunk
+                .emit_op(OpCode::CallNative(0))
+        });
+        directives.register(
+            "assert_stack",
+            Arguments::Constants,
+            |directive, compiler| {
+                let line = compiler
+                    .diagnostics
+                    .borrow()
+                    .line(directive.name.value.span);
+                compiler.emit_assert_stack(directive.constants(), line)
+            },
+        );
+        directives
+    }
+    pub fn register(
+        &mut self,
+        name: &str,
+        arguments: Arguments,
+        emit: fn(directive: &Directive, compiler: &mut Compiler),
+    ) {
+        self.definitions
+            .insert(name.to_owned(), DirectiveDefinition { arguments, emit });
+    }
+    pub fn get(&self, name: &str) -> Option<&DirectiveDefinition> {
+        self.definitions.get(name)
+    }
+}
+
+impl<'a> Compiler<'a> {
+    pub fn emit_assert_stack(&mut self, snapshot: Vec<Value>, line: usize) {
+        let chunk = &mut self.bytecode.function.chunk;
+        let snapshot = chunk.emit_value(Value::Array(rcrf(snapshot)));
+        chunk.emit_op(OpCode::AssertStack(snapshot, line));
+    }
+}
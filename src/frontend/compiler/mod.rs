@@ -1,5 +1,6 @@
-pub mod local;
-use local::{Local, LOCAL};
+pub mod directives;
+
+use directives::Directives;
 
 use std::{cell::RefCell, rc::Rc};
 
@@ -10,10 +11,8 @@ use crate::{
 };
 
 use super::{
-    ast::CompileToBytecode,
-    bytecode::{self, Bytecode},
-    parser::Parser,
-    scanner::Scanner,
+    ast::CompileToBytecode, bytecode::Bytecode, parser::Parser, resolver::Resolver,
+    scanner::Scanner, span::Span,
 };
 
 #[derive(Debug)]
@@ -34,6 +33,17 @@ pub struct Compiler<'a> {
     pub diagnostics: Rc<RefCell<Diagnostics<'a>>>,
     pub interner: Rc<RefCell<StringInterner>>,
     pub bytecode: Bytecode,
+    pub directives: Directives,
+    /// every `test` block compiled so far
+    pub tests: Vec<TestCase>,
+}
+
+/// a compiled `test "name" { ... }` block
+#[derive(Debug, Clone)]
+pub struct TestCase {
+    pub name: String,
+    pub line: usize,
+    pub function: Function,
 }
 
 #[derive(Debug, PartialEq, Clone, Default)]
@@ -41,6 +51,9 @@ pub enum FunctionType {
     #[default]
     Script, // file
     Function,
+    Method,
+    /// the `init` method of a class
+    Initializer,
 }
 
 #[derive(Debug)]
@@ -59,30 +72,67 @@ impl<'a> Compiler<'a> {
             enclosing: None,
             diagnostics,
             interner,
-            bytecode: Bytecode::default(),
+            bytecode: Bytecode {
+                function_type,
+                ..Default::default()
+            },
+            directives: Directives::builtin(),
+            tests: Vec::new(),
         }
     }
 
+    /// points the instructions emitted since `start` at where `span` starts
+    pub fn locate(&mut self, start: usize, span: Span) {
+        let position = self.diagnostics.borrow().position(span);
+        self.bytecode
+            .function
+            .chunk
+            .locate(start, (position.line, position.column));
+    }
     pub fn compile(mut self, source: String) -> Result<Function, CompileResult> {
+        self.compile_file(source)
+    }
+    /// like [`Compiler::compile`], but also hands back the `test` blocks
+    pub fn compile_tests(
+        mut self,
+        source: String,
+    ) -> Result<(Function, Vec<TestCase>), CompileResult> {
+        let function = self.compile_file(source)?;
+        Ok((function, self.tests))
+    }
+    fn compile_file(&mut self, source: String) -> Result<Function, CompileResult> {
+        // spans are `u32`s
+        if u32::try_from(source.len()).is_err() {
+            self.diagnostics.borrow_mut().log(
+                None,
+                "Compiler",
+                String::from("Source files can't be larger than 4 GiB"),
+            );
+            return Err(CompileResult::Error);
+        }
         let scanner = Scanner::new(source);
 
         let parser = Parser::new(
             scanner,
             self.diagnostics.clone(),
             self.bytecode.function_type.clone(),
+            self.directives.clone(),
         );
         self.parser = parser;
 
-        let parsed_file = self.parser.parse_file();
+        let mut parsed_file = self.parser.parse_file();
         if self.parser.had_error {
             return Err(CompileResult::Error);
         }
+        if Resolver::run(&mut parsed_file.nodes, &self.diagnostics).had_error {
+            return Err(CompileResult::Error);
+        }
         let function = Function::new();
         self.bytecode.function = function;
-        parsed_file.to_bytecode(&mut self);
+        parsed_file.to_bytecode(self);
 
         self.bytecode.function.chunk.emit_many(vec![OpCode::Return]);
 
-        Ok(self.bytecode.function)
+        Ok(std::mem::take(&mut self.bytecode.function))
     }
 }
@@ -13,7 +13,9 @@ pub struct FileNode<'a> {
 
 #[derive(Debug, Default)]
 pub struct FileAttributes {
-    pub expect_stack: Option<Vec<Value>>,
+    /// what the stack has to look like once the whole file has run, set by a
+    /// trailing `#assert_stack` at the top level of the file
+    pub expect_stack: Option<(Vec<Value>, usize)>,
 }
 
 impl<'a> CompileToBytecode for FileNode<'a> {
@@ -21,5 +23,8 @@ impl<'a> CompileToBytecode for FileNode<'a> {
         for node in &self.nodes {
             node.to_bytecode(compiler)
         }
+        if let Some((snapshot, line)) = &self.file_attributes.expect_stack {
+            compiler.emit_assert_stack(snapshot.clone(), *line);
+        }
     }
 }
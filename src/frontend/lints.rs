@@ -0,0 +1,34 @@
+//! warnings about code that compiles but probably isn't what was meant, they
+//! run over a whole file once it has parsed
+
+use std::cell::RefCell;
+
+use crate::cli_helper::Diagnostics;
+
+use super::ast::{
+    expression::match_expr::MatchExpr,
+    node::{AstPosition, Node},
+    visit::{self, Visitor},
+};
+
+pub struct Lints<'d, 'a> {
+    diagnostics: &'d RefCell<Diagnostics<'a>>,
+}
+impl<'d, 'a> Lints<'d, 'a> {
+    pub fn run(nodes: &[Node], diagnostics: &'d RefCell<Diagnostics<'a>>) {
+        let mut lints = Lints { diagnostics };
+        for node in nodes {
+            lints.visit_node(node);
+        }
+    }
+}
+impl Visitor for Lints<'_, '_> {
+    fn visit_match(&mut self, match_expr: &MatchExpr) {
+        if !match_expr.is_exhaustive() {
+            self.diagnostics
+                .borrow_mut()
+                .warn(match_expr.span(), "match is not exhaustive, add a `_` arm");
+        }
+        visit::walk_match(self, match_expr)
+    }
+}
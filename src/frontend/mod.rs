@@ -3,8 +3,11 @@ pub mod bytecode;
 pub mod compiler;
 pub mod file;
 pub mod fixedvec;
+pub mod lints;
 pub mod parser;
+pub mod resolver;
 pub mod scanner;
+pub mod span;
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 pub enum Precedence {
     Unimpl,
@@ -12,6 +15,10 @@ pub enum Precedence {
     Assignment,
     Equality,
     Comparison,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Shift,
     Term,
     Factor,
     Unary,
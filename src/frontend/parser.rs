@@ -1,29 +1,51 @@
 /// the parser will make an ast
-use std::{cell::RefCell, mem::transmute, ops::Range, rc::Rc};
+use std::{cell::RefCell, ops::Range, rc::Rc};
 
-use colored::Colorize;
-
-use crate::{cli_helper::Diagnostics, common::opcode::OpCode};
+use crate::cli_helper::Diagnostics;
 
 use super::{
     ast::{
         declaration::{
-            function::FunctionDeclaration, variable_declaration::VariableDeclaration, AsDeclaration,
+            class::ClassDeclaration,
+            enum_declaration::{EnumDeclaration, EnumVariant},
+            function::{FunctionDeclaration, Parameter},
+            test::TestDeclaration,
+            variable_declaration::{Binding, VariableDeclaration},
+            AsDeclaration,
         },
+        directive::Directive,
         expression::{
-            block::Block, call_expr::CallExpr, comparison::Comparison, if_expr::IfExpr,
-            variable_assignment::VariableAssignment, while_expr::WhileExpr, AsExpr, BinaryExpr,
-            Expression,
+            block::Block,
+            call_expr::CallExpr,
+            comparison::Comparison,
+            function_expr::FunctionExpr,
+            if_expr::IfExpr,
+            match_expr::{MatchArm, MatchExpr, Pattern},
+            property::{Invoke, Property, SetProperty},
+            variable_assignment::VariableAssignment,
+            while_expr::WhileExpr,
+            AsExpr, BinaryExpr, Expression,
         },
         identifier::Identifier,
         literal::Literal,
-        node::{AsNode, Node},
-        statement::{return_stmt::ReturnStmt, Statement},
+        node::{AsNode, AstPosition, Node},
+        statement::{
+            assertion::{Assertion, AssertionKind},
+            return_stmt::ReturnStmt,
+            try_catch::TryCatch,
+            Statement,
+        },
         BinaryOperation,
     },
-    compiler::{Compiler, FunctionType},
+    compiler::{
+        directives::{Arguments, Directives},
+        Compiler, FunctionType,
+    },
     file::FileNode,
-    scanner::{Position, Scanner, Token, TokenKind},
+    lints::Lints,
+    resolver::Resolution,
+    scanner::{Scanner, Token, TokenKind},
+    span::Span,
     Precedence,
 };
 
@@ -44,19 +66,28 @@ pub struct Parser<'a> {
 
     pub had_error: bool,
     pub panic_mode: bool,
-    pub scope_depth: usize,
 
     pub function_type: FunctionType,
+    /// the kind of function whose body is being parsed, `Script` outside of one
+    pub current_function: FunctionType,
+    /// whether the infix rule being run may parse an assignment
+    pub can_assign: bool,
+    pub directives: Directives,
 
     pub token_state: TokenState,
 }
 
 #[derive(Debug, Default)]
 pub struct TokenState {
-    pub previous: Rc<Token>,
+    pub previous: Token,
     pub current: Token,
-    pub tokens: Vec<Token>,
-    pub index: usize,
+    /// the token after `current` and its doc comments, filled in by
+    /// [`Parser::peek_token`]
+    pub lookahead: Option<(Token, Vec<String>)>,
+    /// the `///` lines right before `current`
+    pub docs: Vec<String>,
+    /// the `///` lines right before `previous`
+    pub previous_docs: Vec<String>,
 }
 pub struct Rule<'a> {
     pub precedence: Precedence,
@@ -70,32 +101,87 @@ impl<'a> Parser<'a> {
             TokenKind::LeftParen => Rule {
                 precedence: Precedence::Grouping,
                 prefix: Some(|parser: &mut Parser, _can_assign: bool| {
-                    let expr =
-                        Expression::Grouping(Box::new(parser.expression().unwrap().to_expr()))
-                            .to_node();
+                    let start = parser.previous().span;
+                    if parser.match_token(TokenKind::RightParen) {
+                        return Expression::Tuple(Vec::new(), parser.span_from(start)).to_node();
+                    }
+                    let first = parser.expression().unwrap().to_expr();
+                    // `(a, b)` and `(a,)` are tuples, `(a)` is just `a`
+                    if parser.match_token(TokenKind::Comma) {
+                        let mut elements = vec![first];
+                        elements.extend(parser.elements(TokenKind::RightParen));
+                        return Expression::Tuple(elements, parser.span_from(start)).to_node();
+                    }
                     parser.consume(TokenKind::RightParen, "expected right parenthesis to close");
-                    expr
+                    Expression::Grouping(Box::new(first), parser.span_from(start)).to_node()
                 }),
                 infix: Some(Self::call_expr),
             },
+            TokenKind::LeftBracket => Rule {
+                precedence: Precedence::None,
+                prefix: Some(|parser, _can_assign| {
+                    let start = parser.previous().span;
+                    let elements = parser.elements(TokenKind::RightBracket);
+                    Expression::Array(elements, parser.span_from(start)).to_node()
+                }),
+                infix: None,
+            },
             TokenKind::While => Rule {
                 precedence: Precedence::None,
                 prefix: Some(Self::while_expr),
                 infix: None,
             },
+            TokenKind::Func => Rule {
+                precedence: Precedence::None,
+                prefix: Some(Self::function_expr),
+                infix: None,
+            },
+            // `|x| ..` at the start of an expression, bitwise or after one
+            TokenKind::Pipe => Rule {
+                precedence: Precedence::BitOr,
+                prefix: Some(Self::lambda),
+                infix: Some(Self::binary),
+            },
+            TokenKind::Match => Rule {
+                precedence: Precedence::None,
+                prefix: Some(Self::match_expr),
+                infix: None,
+            },
+            TokenKind::Dot => Rule {
+                precedence: Precedence::Grouping,
+                prefix: None,
+                infix: Some(Self::dot),
+            },
+            TokenKind::SelfKeyword => Rule {
+                precedence: Precedence::None,
+                prefix: Some(|parser, _can_assign| {
+                    if !matches!(
+                        parser.current_function,
+                        FunctionType::Method | FunctionType::Initializer
+                    ) {
+                        parser.error("Can't use 'self' outside of a method");
+                    }
+                    Expression::SelfRef(*parser.previous()).to_node()
+                }),
+                infix: None,
+            },
             TokenKind::Greater
             | TokenKind::Less
             | TokenKind::LessEqual
             | TokenKind::GreaterEqual
             | TokenKind::EqualEqual
-            | TokenKind::BangEqual => Rule {
+            | TokenKind::BangEqual
+            | TokenKind::Is => Rule {
                 precedence: Precedence::Comparison,
                 prefix: None,
                 infix: Some(|parser: &mut Parser, lhs: Node| {
                     let comparison_token = parser.previous().kind;
+                    let lhs = lhs.to_expr();
+                    let rhs = parser.expression().unwrap().to_expr();
                     Comparison {
-                        lhs: Box::new(lhs.to_expr()),
-                        rhs: Box::new(parser.expression().unwrap().to_expr()),
+                        span: lhs.span().to(rhs.span()),
+                        lhs: Box::new(lhs),
+                        rhs: Box::new(rhs),
                         kind: comparison_token.try_into().unwrap(),
                     }
                     .to_expr()
@@ -114,27 +200,28 @@ impl<'a> Parser<'a> {
             },
             TokenKind::True => Rule {
                 precedence: Precedence::None,
-                prefix: Some(|_, _| Literal::Bool(true).as_node()),
+                prefix: Some(|parser, _| parser.literal(Literal::Bool(true))),
                 infix: None,
             },
             TokenKind::False => Rule {
                 precedence: Precedence::None,
-                prefix: Some(|_, _| Literal::Bool(false).as_node()),
+                prefix: Some(|parser, _| parser.literal(Literal::Bool(false))),
                 infix: None,
             },
             TokenKind::Identifier => Rule {
                 precedence: Precedence::None,
                 prefix: Some(|parser, can_assign| {
-                    let token = parser.previous().clone();
-                    let _global = parser.scope_depth == 0;
+                    let name = parser.previous_identifier();
                     if can_assign && parser.match_token(TokenKind::Equal) {
+                        let initializer = parser.expression().unwrap().to_expr();
                         return Expression::VariableAssignment(VariableAssignment {
-                            name: Identifier { value: token },
-                            initializer: Box::new(parser.expression().unwrap().to_expr()),
+                            span: name.span().to(initializer.span()),
+                            name,
+                            initializer: Box::new(initializer),
                         })
                         .to_node();
                     }
-                    Identifier { value: token }.to_node()
+                    name.to_node()
                 }),
                 infix: None,
             },
@@ -143,8 +230,31 @@ impl<'a> Parser<'a> {
                 infix: None,
                 prefix: Some(Self::number),
             },
-            TokenKind::Star | TokenKind::Slash => Rule {
-                precedence: Precedence::Factor,
+            // already reported when it was scanned, keep parsing around it
+            TokenKind::Error => Rule {
+                precedence: Precedence::None,
+                infix: None,
+                prefix: Some(|parser, _| parser.literal(Literal::Int(0))),
+            },
+            TokenKind::Star | TokenKind::Slash | TokenKind::TildeSlash | TokenKind::Percent => {
+                Rule {
+                    precedence: Precedence::Factor,
+                    prefix: None,
+                    infix: Some(Self::binary),
+                }
+            }
+            TokenKind::LessLess | TokenKind::GreaterGreater => Rule {
+                precedence: Precedence::Shift,
+                prefix: None,
+                infix: Some(Self::binary),
+            },
+            TokenKind::Ampersand => Rule {
+                precedence: Precedence::BitAnd,
+                prefix: None,
+                infix: Some(Self::binary),
+            },
+            TokenKind::Caret => Rule {
+                precedence: Precedence::BitXor,
                 prefix: None,
                 infix: Some(Self::binary),
             },
@@ -156,20 +266,18 @@ impl<'a> Parser<'a> {
             TokenKind::Dash => Rule {
                 infix: Some(Self::binary),
                 prefix: Some(|parser, _can_assign| {
-                    Expression::Negate(Box::new(
-                        parser.precedence(Precedence::Unary).unwrap().to_expr(),
-                    ))
-                    .to_node()
+                    let start = parser.previous().span;
+                    let operand = parser.precedence(Precedence::Unary).unwrap().to_expr();
+                    Expression::Negate(Box::new(operand), parser.span_from(start)).to_node()
                 }),
                 precedence: Precedence::Term,
             },
             TokenKind::Bang => Rule {
                 precedence: Precedence::Unary,
                 prefix: Some(|parser, _can_assign| {
-                    Expression::Not(Box::new(
-                        parser.precedence(Precedence::Unary).unwrap().to_expr(),
-                    ))
-                    .to_node()
+                    let start = parser.previous().span;
+                    let operand = parser.precedence(Precedence::Unary).unwrap().to_expr();
+                    Expression::Not(Box::new(operand), parser.span_from(start)).to_node()
                 }),
                 infix: None,
             },
@@ -197,25 +305,18 @@ impl<'a> Parser<'a> {
     }
     pub fn precedence(&mut self, prec: Precedence) -> Result<Node, String> {
         self.advance();
-        let _path = {
-            let binding = self.diagnostics.borrow();
-            binding.file_path.to_str().unwrap()
-        };
-        let previous = self.previous();
+        let previous = *self.previous();
         let rule = Self::get_rule(previous.kind);
         let can_assign: bool = prec <= Precedence::Assignment;
         #[allow(unused_assignments)]
         let mut expression: Node = Node::None;
-        if rule.prefix.is_some() {
-            expression = rule.prefix.unwrap()(self, can_assign);
+        if let Some(prefix) = rule.prefix {
+            expression = prefix(self, can_assign);
         } else {
-            return Err(format!(
-                "{} no expr {}:{}:{}",
-                previous.kind,
-                _path,
-                previous.position.line + 1,
-                previous.position.start_in_line + 1,
-            ));
+            // reported rather than panicking, half typed files are parsed all
+            // the time by the language server
+            self.error("Expected an expression");
+            return Ok(self.literal(Literal::Void));
         }
 
         loop {
@@ -241,6 +342,7 @@ impl<'a> Parser<'a> {
             match Self::get_rule(previous.kind).infix {
                 None => {}
                 Some(infix) => {
+                    self.can_assign = can_assign;
                     expression = infix(self, expression);
                 }
             }
@@ -250,7 +352,26 @@ impl<'a> Parser<'a> {
         self.precedence(Precedence::None)
     }
     pub fn parse_file(&mut self) -> FileNode<'a> {
-        let mut file = FileNode::default();
+        let mut file = FileNode {
+            nodes: self.parse_nodes(),
+            ..Default::default()
+        };
+        Lints::run(&file.nodes, &self.diagnostics);
+        // a trailing `#assert_stack` describes how the whole file leaves the stack
+        if let Some(Node::Directive(directive)) = file.nodes.last() {
+            if directive.name() == "assert_stack" && !self.had_error {
+                let line = self.diagnostics.borrow().line(directive.name.value.span);
+                file.file_attributes.expect_stack = Some((directive.constants(), line));
+                file.nodes.pop();
+            }
+        }
+        file
+    }
+
+    /// the top level nodes of the file as they were written, without linting
+    /// them or pulling out the file's attributes like [`Parser::parse_file`]
+    pub fn parse_nodes(&mut self) -> Vec<Node> {
+        let mut nodes = Vec::new();
         self.advance();
         loop {
             if self.at_end() {
@@ -260,51 +381,16 @@ impl<'a> Parser<'a> {
 
             if node.eq(&Node::Empty) {
             } else {
-                file.nodes.push(node);
+                nodes.push(node);
             }
         }
-        file
+        nodes
     }
-
     pub fn node(&mut self) -> Node {
         let node = match self.current().kind {
             TokenKind::Hash => {
                 self.advance();
-                if self.match_token(TokenKind::Identifier) {
-                    match self.previous().lexeme.as_str() {
-                        "void" => {
-                            return Node::Emit(|compiler| {
-                                compiler.bytecode.function.chunk.emit_op(OpCode::Void);
-                            })
-                        }
-                        "debug_stack" => {
-                            return Node::Emit(|compiler| {
-                                compiler
-                                    .bytecode
-                                    .function
-                                    .chunk
-                                    .emit_op(OpCode::CallNative(0))
-                            })
-                        }
-                        "assert_stack" => {
-                            self.consume(TokenKind::LeftBracket, "expected left bracket to close");
-                            let mut exprs = Vec::new();
-                            loop {
-                                if self.match_token(TokenKind::RightBracket) {
-                                    break;
-                                }
-                                exprs.push(self.expression().unwrap());
-                                if !self.match_token(TokenKind::Comma) {
-                                    self.advance();
-                                    break;
-                                }
-                            }
-                            return Node::Emit(|_compiler| todo!());
-                        }
-                        _ => return self.node(),
-                    }
-                }
-                panic!()
+                self.directive()
             }
             _ => self.statement(),
         };
@@ -313,6 +399,49 @@ impl<'a> Parser<'a> {
         }
         node
     }
+    /// parses what comes after a `#`, the arguments depend on how the directive
+    /// was registered
+    pub fn directive(&mut self) -> Node {
+        if !self.match_token(TokenKind::Identifier) {
+            self.error_at_current("Expected a directive name after '#'");
+            return Node::Empty;
+        }
+        let start = self.previous().span;
+        let name = self.previous_identifier();
+        let Some(definition) = self.directives.get(&name.lexeme) else {
+            self.error(format!("Unknown directive `#{}`", name.lexeme).as_str());
+            return Node::Empty;
+        };
+        let arguments = definition.arguments;
+
+        let mut expressions = Vec::new();
+        if arguments != Arguments::None {
+            self.consume(
+                TokenKind::LeftBracket,
+                format!("Expected '[' after #{}", name.lexeme).as_str(),
+            );
+            loop {
+                if self.match_token(TokenKind::RightBracket) {
+                    break;
+                }
+                let expr = self.expression().unwrap().to_expr();
+                if arguments == Arguments::Constants && expr.to_constant().is_none() {
+                    self.error(format!("#{} only accepts constants", name.lexeme).as_str());
+                }
+                expressions.push(expr);
+                if !self.match_token(TokenKind::Comma) {
+                    self.consume(TokenKind::RightBracket, "Expected ']' to close");
+                    break;
+                }
+            }
+        }
+        Directive {
+            name,
+            arguments: expressions,
+            span: self.span_from(start),
+        }
+        .to_node()
+    }
     pub fn expression_statement(&mut self) -> Node {
         let expr = self.expression().unwrap().to_expr();
         self.consume(
@@ -323,12 +452,29 @@ impl<'a> Parser<'a> {
     }
     pub fn token_as_identifier(&mut self) -> Identifier {
         self.advance();
+        self.previous_identifier()
+    }
+    /// the previous token with its name copied out of the source
+    pub fn previous_identifier(&self) -> Identifier {
+        let value = *self.previous();
         Identifier {
-            value: self.previous().clone(),
+            value,
+            lexeme: self.lexeme(&value).to_owned(),
+            resolution: Resolution::Unresolved,
         }
     }
     pub fn statement(&mut self) -> Node {
-        match self.current().kind {
+        // `test` is only a keyword when a name follows it, `let test = 1;` is fine
+        if self.check(TokenKind::Identifier)
+            && self.lexeme(self.current()) == "test"
+            && self.peek_token().kind == TokenKind::String
+        {
+            self.advance();
+            return self.test_declaration();
+        }
+        let start = self.current().span;
+        let kind = self.current().kind;
+        match kind {
             TokenKind::If => {
                 self.advance();
                 self.if_expr(false)
@@ -343,33 +489,26 @@ impl<'a> Parser<'a> {
             }
             TokenKind::Print => {
                 self.advance();
-                let node = Statement::Print(Box::new(self.expression().unwrap())).to_node();
+                let value = Box::new(self.expression().unwrap());
                 self.consume(TokenKind::SemiColon, "Expected ';' ");
-                node
+                Statement::Print(value, self.span_from(start)).to_node()
             }
             TokenKind::AssertEq => {
                 self.advance();
-                let lhs = self.expression().unwrap().to_expr();
-                self.consume(TokenKind::Comma, "Expected ','' to seperate lhs and rhs");
-                let rhs = self.expression().unwrap().to_expr();
-                self.consume(TokenKind::SemiColon, "Expected ';'");
-
-                let node = Statement::AssertEq(lhs, rhs);
-                node.to_node()
+                self.assertion(AssertionKind::Eq)
             }
             TokenKind::AssertNe => {
                 self.advance();
-                let lhs = self.expression().unwrap().to_expr();
-                self.consume(TokenKind::Comma, "Expected ','' to seperate lhs and rhs");
-                let rhs = self.expression().unwrap().to_expr();
-                self.consume(TokenKind::SemiColon, "Expected ';'");
-
-                let node = Statement::AssertNe(lhs, rhs);
-                node.to_node()
+                self.assertion(AssertionKind::Ne)
+            }
+            TokenKind::Assert => {
+                self.advance();
+                self.assertion(AssertionKind::True)
             }
             TokenKind::Let => {
                 self.advance();
-                let identifier = self.token_as_identifier();
+                let doc = self.previous_doc();
+                let binding = self.binding();
                 self.consume(TokenKind::Equal, "Expected '=' after variable name");
                 let initializer = self.expression().unwrap().to_expr();
                 self.consume(
@@ -379,38 +518,55 @@ impl<'a> Parser<'a> {
 
                 VariableDeclaration {
                     intializer: initializer,
-                    identifier,
+                    binding,
+                    doc,
+                    span: self.span_from(start),
                 }
                 .to_node()
             }
-            TokenKind::Func => {
+            // `func(x) { .. }` on its own is an anonymous function expression
+            TokenKind::Func if self.peek_token().kind != TokenKind::LeftParen => {
                 self.advance();
-                let identifier = self.token_as_identifier();
-                let mut parameters: Vec<Identifier> = Vec::new();
-                self.consume(TokenKind::LeftParen, "err");
-                loop {
-                    if self.match_token(TokenKind::RightParen) {
-                        break;
-                    }
-                    parameters.push(self.expression().unwrap().as_identifier());
-                    if !self.match_token(TokenKind::Comma) {
-                        self.advance();
-                        break;
-                    }
-                }
-                self.consume(TokenKind::LeftBrace, "Expected '{'");
-                FunctionDeclaration {
-                    parameters,
-                    name: identifier,
-                    block: self.block(false).to_expr().as_block(),
-                }
-                .to_declaration()
-                .to_node()
+                self.function_declaration(FunctionType::Function)
+                    .to_declaration()
+                    .to_node()
+            }
+            TokenKind::Class => {
+                self.advance();
+                self.class_declaration()
+            }
+            TokenKind::Enum => {
+                self.advance();
+                self.enum_declaration()
+            }
+            TokenKind::Try => {
+                self.advance();
+                self.try_catch()
+            }
+            TokenKind::Match => {
+                // like `if`, a `match` statement doesn't need a `;`
+                self.advance();
+                let match_expr = self.match_expr(false).to_expr();
+                self.match_token(TokenKind::SemiColon);
+                Statement::Expression(match_expr).to_node()
+            }
+            TokenKind::Throw => {
+                self.advance();
+                let value = self.expression().unwrap().to_expr();
+                self.consume(TokenKind::SemiColon, "Expected ';' after the thrown value");
+                Statement::Throw(value, self.span_from(start)).to_node()
             }
             TokenKind::Return => {
                 self.advance();
-                if self.scope_depth == 0 && self.function_type.eq(&FunctionType::Script) {
-                    self.error("Cannot return from the top level of a script")
+                if self.match_token(TokenKind::SemiColon) {
+                    return Statement::Return(ReturnStmt {
+                        expr: None,
+                        span: self.span_from(start),
+                    })
+                    .to_node();
+                }
+                if self.current_function == FunctionType::Initializer {
+                    self.error("Cannot return a value from an initializer")
                 }
                 if let Ok(expr) = self.expression() {
                     let expr = expr.to_expr();
@@ -418,10 +574,18 @@ impl<'a> Parser<'a> {
                         TokenKind::SemiColon,
                         format!("Expected ';' after expression {}:{}", file!(), line!()).as_str(),
                     );
-                    Statement::Return(ReturnStmt { expr: Some(expr) }).to_node()
+                    Statement::Return(ReturnStmt {
+                        expr: Some(expr),
+                        span: self.span_from(start),
+                    })
+                    .to_node()
                 } else {
                     self.consume(TokenKind::SemiColon, "Expected ';' after return statement");
-                    Statement::Return(ReturnStmt { expr: None }).to_node()
+                    Statement::Return(ReturnStmt {
+                        expr: None,
+                        span: self.span_from(start),
+                    })
+                    .to_node()
                 }
             }
             _ => self.expression_statement(),
@@ -429,60 +593,561 @@ impl<'a> Parser<'a> {
     }
 }
 impl Parser<'_> {
-    pub fn call_expr(&mut self, lhs: Node) -> Node {
-        let identifier = lhs.as_identifier();
-        let mut parameters: Vec<Expression> = Vec::new();
+    /// everything after `func`, `function_type` tells whether it's a method
+    pub fn function_declaration(&mut self, function_type: FunctionType) -> FunctionDeclaration {
+        let start = self.previous().span;
+        let doc = self.previous_doc();
+        let identifier = self.token_as_identifier();
+        self.consume(TokenKind::LeftParen, "err");
+        let parameters = self.function_parameters();
+        let block = self.function_body(function_type);
+        FunctionDeclaration {
+            parameters,
+            name: identifier,
+            block,
+            doc,
+            span: self.span_from(start),
+        }
+    }
+    /// a comma separated list of names, up to and including `closing`
+    pub fn parameters(&mut self, closing: TokenKind) -> Vec<Identifier> {
+        let mut parameters: Vec<Identifier> = Vec::new();
+        if self.match_token(closing) {
+            return parameters;
+        }
         loop {
-            if self.match_token(TokenKind::RightParen) {
+            self.consume(TokenKind::Identifier, "Expected a parameter name");
+            parameters.push(self.previous_identifier());
+            if !self.match_token(TokenKind::Comma) {
+                self.consume(closing, "Expected the end of the parameter list");
+                break;
+            }
+        }
+        parameters
+    }
+    /// `(a, b = 1, ...rest)`, after the opening parenthesis
+    pub fn function_parameters(&mut self) -> Vec<Parameter> {
+        let mut parameters: Vec<Parameter> = Vec::new();
+        if self.match_token(TokenKind::RightParen) {
+            return parameters;
+        }
+        loop {
+            let variadic = self.match_token(TokenKind::DotDotDot);
+            self.consume(TokenKind::Identifier, "Expected a parameter name");
+            let name = self.previous_identifier();
+            let default = if self.match_token(TokenKind::Equal) {
+                Some(self.expression().unwrap().to_expr())
+            } else {
+                None
+            };
+            if variadic && default.is_some() {
+                self.error("A variadic parameter can't have a default");
+            }
+            let follows_default = parameters.last().is_some_and(|last| last.default.is_some());
+            if !variadic && default.is_none() && follows_default {
+                self.error("Parameters without a default can't follow ones with a default");
+            }
+            parameters.push(Parameter {
+                name,
+                default,
+                variadic,
+            });
+            if !self.match_token(TokenKind::Comma) {
+                self.consume(
+                    TokenKind::RightParen,
+                    "Expected the end of the parameter list",
+                );
                 break;
             }
+            if variadic {
+                self.error_at_current("The variadic parameter has to be the last one");
+            }
+        }
+        parameters
+    }
+    /// `{ ... }` parsed as the body of a `function_type`
+    fn function_body(&mut self, function_type: FunctionType) -> Block {
+        self.consume(TokenKind::LeftBrace, "Expected '{'");
+        let enclosing = std::mem::replace(&mut self.current_function, function_type);
+        let block = self.block(false).to_expr().as_block();
+        self.current_function = enclosing;
+        block
+    }
+    /// `func(x) { ... }`, after the `func`
+    pub fn function_expr(&mut self, _can_assign: bool) -> Node {
+        let token = *self.previous();
+        self.consume(TokenKind::LeftParen, "Expected '(' after 'func'");
+        let parameters = self.function_parameters();
+        let block = self.function_body(FunctionType::Function);
+        FunctionExpr {
+            token,
+            parameters,
+            block,
+            span: self.span_from(token.span),
+        }
+        .to_expr()
+        .to_node()
+    }
+    /// `|x, y| expr`, after the first `|`
+    pub fn lambda(&mut self, _can_assign: bool) -> Node {
+        let token = *self.previous();
+        let parameters = self
+            .parameters(TokenKind::Pipe)
+            .into_iter()
+            .map(Parameter::from)
+            .collect();
+        let enclosing = std::mem::replace(&mut self.current_function, FunctionType::Function);
+        let body = self.expression().unwrap().to_expr();
+        self.current_function = enclosing;
+        let span = body.span();
+        FunctionExpr {
+            token,
+            parameters,
+            block: Block {
+                declarations: vec![Statement::Return(ReturnStmt {
+                    expr: Some(body),
+                    span,
+                })
+                .to_node()],
+                span,
+                locals: 0,
+            },
+            span: self.span_from(token.span),
+        }
+        .to_expr()
+        .to_node()
+    }
+    pub fn class_declaration(&mut self) -> Node {
+        let start = self.previous().span;
+        let name = self.token_as_identifier();
+        self.consume(TokenKind::LeftBrace, "Expected '{' before the class body");
+        let mut methods = Vec::new();
+        while !self.check(TokenKind::RightBrace) && !self.check(TokenKind::EOF) {
+            self.consume(TokenKind::Func, "Expected 'func' to declare a method");
+            let function_type =
+                if self.check(TokenKind::Identifier) && self.lexeme(self.current()) == "init" {
+                    FunctionType::Initializer
+                } else {
+                    FunctionType::Method
+                };
+            methods.push(self.function_declaration(function_type));
+        }
+        self.consume(TokenKind::RightBrace, "Expected '}' after the class body");
+        ClassDeclaration {
+            name,
+            methods,
+            span: self.span_from(start),
+        }
+        .to_declaration()
+        .to_node()
+    }
+    /// `try { ... } catch (name) { ... }`, after the `try`
+    pub fn try_catch(&mut self) -> Node {
+        let start = self.previous().span;
+        self.consume(TokenKind::LeftBrace, "Expected '{' after 'try'");
+        let body = self.block(false).to_expr().as_block();
+        self.consume(TokenKind::Catch, "Expected 'catch' after the try block");
+        self.consume(TokenKind::LeftParen, "Expected '(' after 'catch'");
+        self.consume(
+            TokenKind::Identifier,
+            "Expected a name for the caught error",
+        );
+        let binding = self.previous_identifier();
+        self.consume(TokenKind::RightParen, "Expected ')' after the error's name");
+        self.consume(TokenKind::LeftBrace, "Expected '{' after 'catch (..)'");
+        let handler = self.block(false).to_expr().as_block();
+        Statement::Try(Box::new(TryCatch {
+            body,
+            binding,
+            handler,
+            span: self.span_from(start),
+        }))
+        .to_node()
+    }
+    /// `match value { pattern if guard => body, ... }`, after the `match`
+    pub fn match_expr(&mut self, _can_assign: bool) -> Node {
+        let token = *self.previous();
+        let value = Box::new(self.expression().unwrap().to_expr());
+        self.consume(TokenKind::LeftBrace, "Expected '{' after the matched value");
+        let mut arms = Vec::new();
+        while !self.check(TokenKind::RightBrace) && !self.check(TokenKind::EOF) {
+            let start = self.current().span;
+            let pattern = self.pattern();
+            let guard = if self.match_token(TokenKind::If) {
+                Some(self.expression().unwrap().to_expr())
+            } else {
+                None
+            };
+            self.consume(TokenKind::FatArrow, "Expected '=>' after the pattern");
+            let body = self.expression().unwrap().to_expr();
+            // a comma is optional after `{ }` bodies
+            let braced = matches!(
+                body,
+                Expression::Block(_) | Expression::If(_) | Expression::While(_)
+            );
+            arms.push(MatchArm {
+                pattern,
+                guard,
+                body,
+                span: self.span_from(start),
+            });
+            if !self.match_token(TokenKind::Comma) && !braced {
+                break;
+            }
+        }
+        self.consume(TokenKind::RightBrace, "Expected '}' after the match arms");
 
-            let parameter = self.expression();
-            parameters.push(parameter.unwrap().to_expr());
+        MatchExpr {
+            token,
+            value,
+            arms,
+            span: self.span_from(token.span),
+            slot: 0,
+        }
+        .to_expr()
+        .to_node()
+    }
+    /// one or more patterns separated by `|`
+    fn pattern(&mut self) -> Pattern {
+        let first = self.single_pattern();
+        if !self.check(TokenKind::Pipe) {
+            return first;
+        }
+        let mut alternatives = vec![first];
+        while self.match_token(TokenKind::Pipe) {
+            alternatives.push(self.single_pattern());
+        }
+        if alternatives
+            .iter()
+            .any(|pattern| matches!(pattern, Pattern::Binding(_)))
+        {
+            self.error("Alternatives can't bind a name, use `_` instead");
+        }
+        Pattern::Alternatives(alternatives)
+    }
+    fn single_pattern(&mut self) -> Pattern {
+        self.advance();
+        let token = *self.previous();
+        match token.kind {
+            TokenKind::Identifier if self.lexeme(&token) == "_" => Pattern::Wildcard,
+            TokenKind::Identifier => Pattern::Binding(self.previous_identifier()),
+            TokenKind::True => Pattern::Literal(Literal::Bool(true), token.span),
+            TokenKind::False => Pattern::Literal(Literal::Bool(false), token.span),
+            TokenKind::String => {
+                Pattern::Literal(Literal::String(self.lexeme(&token).to_owned()), token.span)
+            }
+            TokenKind::Number | TokenKind::Dash => {
+                let start = self.pattern_number();
+                let inclusive = if self.match_token(TokenKind::DotDotEqual) {
+                    true
+                } else if self.match_token(TokenKind::DotDot) {
+                    false
+                } else {
+                    return Pattern::Literal(start, self.span_from(token.span));
+                };
+                self.advance();
+                let end = self.pattern_number();
+                Pattern::Range {
+                    start,
+                    end,
+                    inclusive,
+                    span: self.span_from(token.span),
+                }
+            }
+            _ => {
+                self.error("Expected a pattern");
+                Pattern::Wildcard
+            }
+        }
+    }
+    /// a number in a pattern, the previous token is the number or a leading `-`
+    fn pattern_number(&mut self) -> Literal {
+        let negative = self.previous().kind == TokenKind::Dash;
+        if negative {
+            self.consume(TokenKind::Number, "Expected a number after '-'");
+        } else if self.previous().kind != TokenKind::Number {
+            self.error("Expected a number");
+            return Literal::Int(0);
+        }
+        match self.number_literal() {
+            Literal::Int(int) if negative => Literal::Int(-int),
+            Literal::Number(number) if negative => Literal::Number(-number),
+            literal => literal,
+        }
+    }
+    /// `enum Name { Variant(field, ..), Variant, .. }`, after the `enum`
+    pub fn enum_declaration(&mut self) -> Node {
+        let start = self.previous().span;
+        let name = self.token_as_identifier();
+        self.consume(
+            TokenKind::LeftBrace,
+            "Expected '{' before the enum's variants",
+        );
+        let mut variants = Vec::new();
+        while !self.check(TokenKind::RightBrace) && !self.check(TokenKind::EOF) {
+            self.consume(TokenKind::Identifier, "Expected a variant name");
+            let name = self.previous_identifier();
+            let fields = if self.match_token(TokenKind::LeftParen) {
+                self.parameters(TokenKind::RightParen)
+            } else {
+                Vec::new()
+            };
+            variants.push(EnumVariant { name, fields });
             if !self.match_token(TokenKind::Comma) {
+                break;
+            }
+        }
+        self.consume(
+            TokenKind::RightBrace,
+            "Expected '}' after the enum's variants",
+        );
+        EnumDeclaration {
+            name,
+            variants,
+            span: self.span_from(start),
+        }
+        .to_declaration()
+        .to_node()
+    }
+    /// the positional and `name: value` arguments of a call, after the
+    /// opening parenthesis
+    pub fn arguments(&mut self) -> (Vec<Expression>, Vec<(Identifier, Expression)>) {
+        let mut positional: Vec<Expression> = Vec::new();
+        let mut named: Vec<(Identifier, Expression)> = Vec::new();
+        loop {
+            if self.match_token(TokenKind::RightParen) {
+                break;
+            }
+
+            if self.check(TokenKind::Identifier) && self.peek_token().kind == TokenKind::Colon {
+                let name = self.token_as_identifier();
                 self.advance();
+                named.push((name, self.expression().unwrap().to_expr()));
+            } else {
+                if !named.is_empty() {
+                    self.error_at_current("Positional arguments can't follow named ones");
+                }
+                positional.push(self.expression().unwrap().to_expr());
+            }
+            if !self.match_token(TokenKind::Comma) {
+                self.consume(TokenKind::RightParen, "Expected ')' after the arguments");
+                break;
+            }
+        }
+        (positional, named)
+    }
+    /// comma separated expressions up to and including `closing`, a trailing
+    /// comma is fine
+    pub fn elements(&mut self, closing: TokenKind) -> Vec<Expression> {
+        let mut elements: Vec<Expression> = Vec::new();
+        loop {
+            if self.match_token(closing) {
+                break;
+            }
+
+            let element = self.expression();
+            elements.push(element.unwrap().to_expr());
+            if !self.match_token(TokenKind::Comma) {
+                self.consume(closing, "Expected ',' or a closing bracket");
                 break;
             }
         }
+        elements
+    }
+    /// what comes after `let`, a name, `(a, b)` or `[first, rest...]`
+    pub fn binding(&mut self) -> Binding {
+        if self.match_token(TokenKind::LeftParen) {
+            return Binding::Tuple(self.parameters(TokenKind::RightParen));
+        }
+        if !self.match_token(TokenKind::LeftBracket) {
+            return Binding::Name(self.token_as_identifier());
+        }
+        let mut names = Vec::new();
+        let mut rest = None;
+        while !self.match_token(TokenKind::RightBracket) {
+            self.consume(TokenKind::Identifier, "Expected a name to bind");
+            let name = self.previous_identifier();
+            if self.match_token(TokenKind::DotDotDot) {
+                rest = Some(name);
+                self.consume(
+                    TokenKind::RightBracket,
+                    "Expected ']' after the rest binding",
+                );
+                break;
+            }
+            names.push(name);
+            if !self.match_token(TokenKind::Comma) {
+                self.consume(TokenKind::RightBracket, "Expected ']' after the names");
+                break;
+            }
+        }
+        Binding::Array { names, rest }
+    }
+    /// `object.name`, `object.name = value` or `object.name(args)`
+    pub fn dot(&mut self, lhs: Node) -> Node {
+        let object = Box::new(lhs.to_expr());
+        // numbers are positional fields, like `shape.0`
+        if !self.match_token(TokenKind::Number) {
+            self.consume(TokenKind::Identifier, "Expected a property name after '.'");
+        }
+        let name = self.previous_identifier();
+        if self.can_assign && self.match_token(TokenKind::Equal) {
+            let value = Box::new(self.expression().unwrap().to_expr());
+            SetProperty {
+                span: object.span().to(value.span()),
+                object,
+                name,
+                value,
+            }
+            .to_expr()
+            .to_node()
+        } else if self.match_token(TokenKind::LeftParen) {
+            let (arguments, named) = self.arguments();
+            Invoke {
+                span: object.span().to(self.previous().span),
+                object,
+                name,
+                arguments,
+                named,
+            }
+            .to_expr()
+            .to_node()
+        } else {
+            Property {
+                span: object.span().to(name.value.span),
+                object,
+                name,
+            }
+            .to_expr()
+            .to_node()
+        }
+    }
+    /// parses an expression and also returns how it was written
+    pub fn expression_with_source(&mut self) -> (Expression, String) {
+        let start = self.current().span.start as usize;
+        let expr = self.expression().unwrap().to_expr();
+        let previous = self.previous();
+        let end = previous.span.end();
+        (expr, self.scanner.source[start..end.max(start)].to_string())
+    }
+    pub fn assertion(&mut self, kind: AssertionKind) -> Node {
+        let token = *self.previous();
+        let (lhs, lhs_source) = self.expression_with_source();
+        let (rhs, source) = match kind {
+            AssertionKind::True => (None, lhs_source),
+            AssertionKind::Eq | AssertionKind::Ne => {
+                self.consume(TokenKind::Comma, "Expected ',' to seperate lhs and rhs");
+                let (rhs, rhs_source) = self.expression_with_source();
+                let op = if kind == AssertionKind::Eq {
+                    "=="
+                } else {
+                    "!="
+                };
+                (Some(rhs), format!("{lhs_source} {op} {rhs_source}"))
+            }
+        };
+        let message = if self.match_token(TokenKind::Comma) {
+            Some(self.expression().unwrap().to_expr())
+        } else {
+            None
+        };
+        self.consume(TokenKind::SemiColon, "Expected ';'");
+        Statement::Assert(Box::new(Assertion {
+            kind,
+            lhs,
+            rhs,
+            message,
+            source,
+            token,
+            span: self.span_from(token.span),
+        }))
+        .to_node()
+    }
+    pub fn test_declaration(&mut self) -> Node {
+        let token = *self.previous();
+        self.consume(TokenKind::String, "Expected the name of the test");
+        let name = self.lexeme(self.previous()).to_owned();
+        self.consume(TokenKind::LeftBrace, "Expected '{'");
+        let block = self.block(false).to_expr().as_block();
+        TestDeclaration {
+            name,
+            token,
+            block,
+            span: self.span_from(token.span),
+        }
+        .to_declaration()
+        .to_node()
+    }
+    pub fn call_expr(&mut self, lhs: Node) -> Node {
+        let callee = Box::new(lhs.to_expr());
+        let (parameters, named) = self.arguments();
         CallExpr {
+            span: callee.span().to(self.previous().span),
             parameters: Box::new(parameters),
-            identifier,
+            callee,
+            named,
         }
         .to_expr()
         .to_node()
     }
     pub fn while_expr(&mut self, _can_assign: bool) -> Node {
+        let start = self.previous().span;
         let condition = self.expression().unwrap().to_expr();
-        let block = self.expression().unwrap().to_expr().as_block();
+        let block = self.body("Expected '{' after the loop's condition");
 
         WhileExpr {
             predicate: Box::new(condition),
             block,
+            span: self.span_from(start),
         }
         .to_expr()
         .to_node()
     }
     pub fn if_expr(&mut self, _can_assign: bool) -> Node {
+        let start = self.previous().span;
         let condition = self.expression().unwrap().to_expr();
-        let then = self.expression().unwrap().to_expr().as_block();
-        #[allow(unused_mut)]
+        let then = self.body("Expected '{' after the condition");
         let mut else_block = None;
         if self.match_token(TokenKind::Else) {
-            let block = self.expression().unwrap().to_expr().as_block();
-            else_block = Some(block)
+            else_block = Some(if self.match_token(TokenKind::If) {
+                // `else if` is an else block with just the inner `if`
+                let inner = self.if_expr(false);
+                Block {
+                    span: inner.span(),
+                    declarations: vec![inner],
+                    locals: 0,
+                }
+            } else {
+                self.body("Expected '{' or 'if' after 'else'")
+            });
         }
         IfExpr {
             predicate: Box::new(condition),
             then,
             else_block,
+            span: self.span_from(start),
         }
         .to_expr()
         .to_node()
     }
+    /// the `{ ... }` body of an `if` or `while`, an empty one if it's missing
+    fn body(&mut self, msg: &str) -> Block {
+        if !self.match_token(TokenKind::LeftBrace) {
+            self.error_at_current(msg);
+            return Block {
+                declarations: Vec::new(),
+                span: self.current().span,
+                locals: 0,
+            };
+        }
+        self.block(false).to_expr().as_block()
+    }
     pub fn block(&mut self, _can_assign: bool) -> Node {
-        self.begin_scope();
+        let start = self.previous().span;
         let mut block = Block {
             declarations: Vec::new(),
+            span: start,
+            locals: 0,
         };
         loop {
             if !self.check(TokenKind::RightBrace) && !self.check(TokenKind::EOF) {
@@ -492,11 +1157,11 @@ impl Parser<'_> {
             }
         }
         self.consume(TokenKind::RightBrace, "Expected '}' after block to close");
-        self.end_scope();
+        block.span = self.span_from(start);
         block.to_node()
     }
     pub fn string(&mut self, _can_assign: bool) -> Node {
-        Literal::String(self.previous().lexeme.clone()).as_node()
+        self.literal(Literal::String(self.lexeme(self.previous()).to_owned()))
     }
     pub fn binary(&mut self, lhs: Node) -> Node {
         let rule = Self::get_rule(self.previous().kind);
@@ -505,14 +1170,21 @@ impl Parser<'_> {
             TokenKind::Dash => BinaryOperation::Subtract,
             TokenKind::Star => BinaryOperation::Multiply,
             TokenKind::Slash => BinaryOperation::Divide,
+            TokenKind::TildeSlash => BinaryOperation::IntDivide,
+            TokenKind::Percent => BinaryOperation::Remainder,
+            TokenKind::Ampersand => BinaryOperation::BitAnd,
+            TokenKind::Pipe => BinaryOperation::BitOr,
+            TokenKind::Caret => BinaryOperation::BitXor,
+            TokenKind::LessLess => BinaryOperation::ShiftLeft,
+            TokenKind::GreaterGreater => BinaryOperation::ShiftRight,
             _ => panic!(),
         };
-        // the precedence is +1 so it'll compile it as the rhs
-        #[allow(unsafe_code)]
-        let prec: Precedence = unsafe { transmute((rule.precedence as u8) + 1) };
-        let rhs = self.precedence(prec).unwrap();
+        // parsing stops at operators that don't bind tighter, so `1 - 2 - 3`
+        // groups to the left and `1 + 2 * 3` keeps the `*` in the rhs
+        let rhs = self.precedence(rule.precedence).unwrap();
 
         Expression::Binary(BinaryExpr {
+            span: lhs.span().to(rhs.span()),
             lhs: Box::new(lhs),
             rhs: Box::new(rhs),
             op,
@@ -520,67 +1192,88 @@ impl Parser<'_> {
         .to_node()
     }
     pub fn number(&mut self, _can_assign: bool) -> Node {
-        Literal::Number(self.previous().lexeme.parse::<f64>().unwrap()).as_node()
+        let literal = self.number_literal();
+        self.literal(literal)
+    }
+    /// the previous number token, an int unless it has a fractional part or
+    /// an exponent, the scanner already rejected malformed ones
+    fn number_literal(&mut self) -> Literal {
+        let lexeme = self.lexeme(self.previous()).replace('_', "");
+        let (digits, radix) = match lexeme.get(..2) {
+            Some("0x") => (&lexeme[2..], 16),
+            Some("0b") => (&lexeme[2..], 2),
+            Some("0o") => (&lexeme[2..], 8),
+            _ if lexeme.contains(['.', 'e', 'E']) => {
+                return match lexeme.parse::<f64>() {
+                    Ok(number) => Literal::Number(number),
+                    Err(_) => {
+                        self.error("Malformed number");
+                        Literal::Number(0.0)
+                    }
+                };
+            }
+            _ => (lexeme.as_str(), 10),
+        };
+        match i64::from_str_radix(digits, radix) {
+            Ok(int) => Literal::Int(int),
+            Err(_) => {
+                self.error("Integer literal is too large");
+                Literal::Int(0)
+            }
+        }
     }
 }
-const EOF: &Token = &Token {
+const EOF: Token = Token {
     kind: TokenKind::EOF,
-    lexeme: String::new(),
-    line: 0,
-    length: 0,
-    position: Position {
-        line: 0,
-        start_in_source: 0,
-        start_in_line: 0,
-    },
+    span: Span { start: 0, len: 0 },
 };
 macro_rules! error_at_current {
     ($parser:expr, $msg:expr) => {{
         $parser.had_error = true;
-        let current = $parser.current().to_owned();
+        let current = *$parser.current();
         error_at!($parser, &current, $msg);
     }};
 }
 macro_rules! error_at {
     ($parser:expr, $token:expr, $msg:expr) => {{
+        // the first error is the useful one, the rest follow from it until
+        // the parser synchronizes
+        if $parser.panic_mode {
+            return;
+        }
         $parser.panic_mode = true;
         let mut diagnostics = $parser.diagnostics.borrow_mut();
 
         match $token.kind {
             TokenKind::EOF => {
                 diagnostics.log(
-                    Some(&$token.position),
+                    Some($token.span),
                     "Compiler",
-                    "Error at EOF: ".to_string(),
+                    format!("Error at EOF: {}", $msg),
                 );
             }
 
             _ => {
-                let range: Range<usize> = ($token.position.start_in_source as usize)
-                    ..($token.position.start_in_source as usize + $token.length);
+                let range: Range<usize> = $token.span.range();
                 diagnostics.log(
-                    Some(&$token.position),
+                    Some($token.span),
                     "Compiler",
-                    format!("Error at `{}`: ", &$parser.scanner.source[range]),
+                    format!("Error at `{}`: {}", &$parser.scanner.source[range], $msg),
                 );
             }
         }
-        println!("{} {}:{}", $msg.red(), file!(), line!());
     }};
 }
 impl<'a> Parser<'a> {
-    pub fn begin_scope(&mut self) {
-        self.scope_depth += 1;
-    }
-    pub fn end_scope(&mut self) {
-        self.scope_depth -= 1;
-    }
     pub fn error(&mut self, msg: &str) {
         self.had_error = true;
 
-        let previous = self.previous().to_owned();
+        let previous = *self.previous();
         error_at!(self, &previous, msg);
     }
+    pub fn error_at_current(&mut self, msg: &str) {
+        error_at_current!(self, msg);
+    }
 
     pub fn synchronize(&mut self) {
         self.panic_mode = false;
@@ -591,9 +1284,14 @@ impl<'a> Parser<'a> {
             if self.previous().kind.eq(&TokenKind::SemiColon) {
                 return;
             };
-            println!("{}", self.current().kind);
             match self.current().kind {
-                TokenKind::Return | TokenKind::Print | TokenKind::Func | TokenKind::Let => {
+                TokenKind::Return
+                | TokenKind::Print
+                | TokenKind::Func
+                | TokenKind::Let
+                | TokenKind::Assert
+                | TokenKind::AssertEq
+                | TokenKind::AssertNe => {
                     return;
                 }
                 _ => {}
@@ -607,19 +1305,24 @@ impl<'a> Parser<'a> {
         scanner: Scanner,
         diagnostics: Rc<RefCell<Diagnostics<'a>>>,
         function_type: FunctionType,
+        directives: Directives,
     ) -> Parser<'a> {
+        diagnostics.borrow_mut().set_source(&scanner.source);
         Parser {
             diagnostics,
             scanner,
             had_error: false,
             panic_mode: false,
-            scope_depth: 0,
             function_type,
+            current_function: FunctionType::Script,
+            can_assign: false,
+            directives,
             token_state: TokenState {
-                current: EOF.to_owned(),
-                previous: Rc::new(EOF.to_owned()),
-                tokens: Vec::new(),
-                index: 0,
+                current: EOF,
+                previous: EOF,
+                lookahead: None,
+                docs: Vec::new(),
+                previous_docs: Vec::new(),
             },
         }
     }
@@ -641,6 +1344,14 @@ impl<'a> Parser<'a> {
     pub fn current(&self) -> &Token {
         &self.token_state.current
     }
+    /// from `start` to the end of the previous token
+    pub fn span_from(&self, start: Span) -> Span {
+        start.to(self.previous().span)
+    }
+    /// a literal written as the previous token
+    fn literal(&self, literal: Literal) -> Node {
+        Expression::Literal(literal, self.previous().span).to_node()
+    }
 
     pub fn consume(&mut self, kind: TokenKind, err: &str) {
         let current = self.current().kind;
@@ -651,21 +1362,56 @@ impl<'a> Parser<'a> {
         self.advance();
     }
 
-    pub fn peek(&mut self, distance: usize) -> &Token {
-        &self.scanner.tokens[self.scanner.tokens.len() - (1 + distance)]
+    /// the doc comment of the previous token, `None` if it doesn't have one
+    pub fn previous_doc(&self) -> Option<String> {
+        let docs = &self.token_state.previous_docs;
+        (!docs.is_empty()).then(|| docs.join("\n"))
     }
 }
 
 impl<'a> Parser<'a> {
     pub fn advance(&mut self) -> &Token {
-        let current = self.scanner.next_token();
-        self.token_state
-            .tokens
-            .insert(self.token_state.index, self.token_state.current.to_owned());
-        self.token_state.previous =
-            Rc::new(self.token_state.tokens[self.token_state.index].to_owned());
-        self.token_state.current = current;
-        self.token_state.index += 1;
+        let (current, docs) = match self.token_state.lookahead.take() {
+            Some(lookahead) => lookahead,
+            None => self.scan_token(),
+        };
+        if current.kind == TokenKind::Error {
+            self.had_error = true;
+            self.diagnostics.borrow_mut().log(
+                Some(current.span),
+                "Compiler",
+                format!(
+                    "Error at `{}`: {}",
+                    &self.scanner.source[current.span.range()],
+                    self.scanner.lexeme(&current)
+                ),
+            );
+        }
+        self.token_state.previous_docs = std::mem::replace(&mut self.token_state.docs, docs);
+        self.token_state.previous = std::mem::replace(&mut self.token_state.current, current);
         &self.token_state.current
     }
+    /// the token after the current one, without moving past it
+    pub fn peek_token(&mut self) -> Token {
+        if self.token_state.lookahead.is_none() {
+            self.token_state.lookahead = Some(self.scan_token());
+        }
+        self.token_state.lookahead.as_ref().unwrap().0
+    }
+    /// the next token from the scanner, doc comments aren't part of the
+    /// grammar so they're handed back for whatever declaration comes next
+    fn scan_token(&mut self) -> (Token, Vec<String>) {
+        let mut docs = Vec::new();
+        loop {
+            let token = self.scanner.next_token();
+            if token.kind != TokenKind::DocComment {
+                break (token, docs);
+            }
+            docs.push(self.scanner.lexeme(&token).to_owned());
+        }
+    }
+    /// see [`Scanner::lexeme`]
+    pub fn lexeme(&self, token: &Token) -> &str {
+        self.scanner.lexeme(token)
+    }
 }
@@ -0,0 +1,602 @@
+//! works out what every name refers to, between parsing and codegen
+//!
+//! the resolver walks a parsed file once, keeping the scopes of every function
+//! it's in, gives each local the stack slot codegen will put it in and writes
+//! the answer into the [`Identifier`]s themselves, so codegen never has to
+//! look a name up. what it found is kept in a [`SymbolTable`] for tools like
+//! the language server
+
+use std::{cell::RefCell, collections::HashMap};
+
+use crate::cli_helper::Diagnostics;
+
+use super::{
+    ast::{
+        declaration::{
+            class::ClassDeclaration,
+            enum_declaration::EnumDeclaration,
+            function::{FunctionDeclaration, Parameter},
+            test::TestDeclaration,
+            variable_declaration::VariableDeclaration,
+        },
+        expression::{
+            block::Block,
+            call_expr::CallExpr,
+            comparison::Comparison,
+            function_expr::FunctionExpr,
+            match_expr::{MatchArm, MatchExpr, Pattern},
+            property::{Invoke, SetProperty},
+            BinaryExpr, Expression,
+        },
+        identifier::Identifier,
+        node::{AstPosition, Node},
+        statement::{assertion::Assertion, return_stmt::ReturnStmt, try_catch::TryCatch},
+        visit_mut::{self, VisitorMut},
+    },
+    span::Span,
+};
+
+/// how many locals a function can have at once
+const MAX_LOCALS: u16 = 512;
+
+/// where the value of a name lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Resolution {
+    /// the parser leaves every name like this, codegen only sees resolved ones
+    #[default]
+    Unresolved,
+    /// a slot in the stack frame of the function it's used in
+    Local(u16),
+    /// looked up by name when it runs
+    Global,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Variable,
+    Parameter,
+    Function,
+    Class,
+    Method,
+    Enum,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// the top level of the file, where names are globals
+    File,
+    /// the parameters of a function, method or test
+    Function,
+    /// a block, a match arm or a catch
+    Block,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub parent: Option<usize>,
+    pub span: Span,
+}
+
+/// a declared name
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// the name where it's declared
+    pub span: Span,
+    /// the whole declaration the name is part of
+    pub declaration: Span,
+    /// the declaration up to its body or initializer, `func add(a, b)`
+    pub signature: Span,
+    pub doc: Option<String>,
+    pub scope: usize,
+    /// `Unresolved` for methods, they're looked up on instances
+    pub resolution: Resolution,
+    /// the function, method or class it was declared in
+    pub parent: Option<usize>,
+}
+
+/// a name being read or assigned
+#[derive(Debug, Clone, PartialEq)]
+pub struct Use {
+    pub span: Span,
+    /// `None` for globals that are never declared in the file, like natives
+    pub symbol: Option<usize>,
+}
+
+/// a function using a local of a function it's declared in, functions don't
+/// close over anything yet so these are reported as errors
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture {
+    pub span: Span,
+    pub symbol: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SymbolTable {
+    pub symbols: Vec<Symbol>,
+    /// the file's scope first
+    pub scopes: Vec<Scope>,
+    /// in the order they're written
+    pub uses: Vec<Use>,
+    pub captures: Vec<Capture>,
+}
+impl SymbolTable {
+    /// the symbol declared or used at `offset`
+    pub fn symbol_at(&self, offset: u32) -> Option<usize> {
+        let contains = |span: &Span| span.start <= offset && offset as usize <= span.end();
+        self.uses
+            .iter()
+            .find(|used| contains(&used.span))
+            .and_then(|used| used.symbol)
+            .or_else(|| {
+                self.symbols
+                    .iter()
+                    .position(|symbol| contains(&symbol.span))
+            })
+    }
+}
+
+/// the scopes of one function being resolved
+#[derive(Debug)]
+struct Frame {
+    /// innermost last, empty at the top level of the file
+    scopes: Vec<FrameScope>,
+    /// the slot the next local goes in
+    slots: u16,
+}
+#[derive(Debug)]
+struct FrameScope {
+    /// where it is in [`SymbolTable::scopes`]
+    index: usize,
+    /// the symbols visible in it so far
+    symbols: Vec<usize>,
+    /// the free slot when the scope started, everything from there on is
+    /// popped at its end
+    slots: u16,
+}
+
+pub struct Resolver<'d, 'a> {
+    diagnostics: &'d RefCell<Diagnostics<'a>>,
+    pub table: SymbolTable,
+    pub had_error: bool,
+    /// the functions being resolved, the file itself first
+    frames: Vec<Frame>,
+    /// the function, method or class being resolved
+    parent: Option<usize>,
+    /// uses of names that aren't locals, globals can be used before they're
+    /// declared so these are matched up once the whole file is resolved
+    globals: Vec<(usize, String)>,
+}
+impl<'d, 'a> Resolver<'d, 'a> {
+    pub fn run(nodes: &mut [Node], diagnostics: &'d RefCell<Diagnostics<'a>>) -> Self {
+        let span = nodes.first().map_or(Span::default(), |first| {
+            first.span().to(nodes.last().unwrap().span())
+        });
+        let mut resolver = Resolver {
+            diagnostics,
+            table: SymbolTable::default(),
+            had_error: false,
+            frames: vec![Frame {
+                scopes: Vec::new(),
+                slots: 0,
+            }],
+            parent: None,
+            globals: Vec::new(),
+        };
+        resolver.table.scopes.push(Scope {
+            kind: ScopeKind::File,
+            parent: None,
+            span,
+        });
+        for node in nodes {
+            resolver.visit_node(node);
+        }
+        resolver.resolve_globals();
+        resolver
+    }
+    fn error(&mut self, span: Span, msg: &str) {
+        self.had_error = true;
+        let mut diagnostics = self.diagnostics.borrow_mut();
+        let text = diagnostics.line_index.text(span).to_owned();
+        diagnostics.log(Some(span), "Compiler", format!("Error at `{text}`: {msg}"));
+    }
+    fn frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().unwrap()
+    }
+    /// the scope declarations go into right now
+    fn scope(&self) -> usize {
+        self.frames
+            .iter()
+            .rev()
+            .find_map(|frame| frame.scopes.last())
+            .map_or(0, |scope| scope.index)
+    }
+    /// at the top level of the file, outside of any block or function
+    fn at_top_level(&self) -> bool {
+        self.frames.len() == 1 && self.frames[0].scopes.is_empty()
+    }
+    fn push_scope(&mut self, kind: ScopeKind, span: Span) {
+        let index = self.table.scopes.len();
+        let parent = Some(self.scope());
+        self.table.scopes.push(Scope { kind, parent, span });
+        let frame = self.frame();
+        frame.scopes.push(FrameScope {
+            index,
+            symbols: Vec::new(),
+            slots: frame.slots,
+        });
+    }
+    /// forgets the scope's locals, their slots are free again, gives back
+    /// how many there were for codegen to pop
+    fn pop_scope(&mut self) -> u16 {
+        let frame = self.frame();
+        let scope = frame.scopes.pop().unwrap();
+        let locals = frame.slots - scope.slots;
+        frame.slots = scope.slots;
+        locals
+    }
+    fn scoped(&mut self, span: Span, resolve: impl FnOnce(&mut Self)) -> u16 {
+        self.push_scope(ScopeKind::Block, span);
+        resolve(self);
+        self.pop_scope()
+    }
+    /// a fresh frame for a function body, it can't see the locals around it
+    fn function(&mut self, symbol: Option<usize>, span: Span, resolve: impl FnOnce(&mut Self)) {
+        let parent = self.parent;
+        self.parent = symbol.or(parent);
+        self.frames.push(Frame {
+            scopes: Vec::new(),
+            slots: 0,
+        });
+        self.push_scope(ScopeKind::Function, span);
+        resolve(self);
+        self.frames.pop();
+        self.parent = parent;
+    }
+    /// an operand stays on the stack while the rest of its operation runs, so
+    /// it takes up a slot until the operation [consumes](Self::consume_operands) it
+    fn operand(&mut self, operand: &mut Expression) {
+        self.visit_expression(operand);
+        self.frame().slots += 1;
+    }
+    fn consume_operands(&mut self, count: usize) {
+        self.frame().slots -= count as u16;
+    }
+    /// records a symbol, it can't be used until it's [bound](Self::bind)
+    fn add_symbol(
+        &mut self,
+        name: &Identifier,
+        kind: SymbolKind,
+        declaration: Span,
+        signature: Span,
+        doc: Option<String>,
+    ) -> usize {
+        let scope = self.scope();
+        self.table.symbols.push(Symbol {
+            name: name.lexeme.clone(),
+            kind,
+            span: name.span(),
+            declaration,
+            signature,
+            doc,
+            scope,
+            resolution: Resolution::Unresolved,
+            parent: self.parent,
+        });
+        self.table.symbols.len() - 1
+    }
+    /// gives the symbol the next free slot, or makes it a global at the top
+    /// level, which is what codegen does when it declares it
+    fn bind(&mut self, symbol: usize, name: &mut Identifier) {
+        let resolution = if self.frame().scopes.is_empty() {
+            Resolution::Global
+        } else {
+            let frame = self.frame();
+            let slot = frame.slots;
+            frame.slots += 1;
+            frame.scopes.last_mut().unwrap().symbols.push(symbol);
+            if slot >= MAX_LOCALS {
+                self.error(name.span(), "Too many locals in one function");
+            }
+            Resolution::Local(slot)
+        };
+        self.table.symbols[symbol].resolution = resolution;
+        name.resolution = resolution;
+    }
+    fn declare(
+        &mut self,
+        name: &mut Identifier,
+        kind: SymbolKind,
+        declaration: Span,
+        signature: Span,
+        doc: Option<String>,
+    ) {
+        let symbol = self.add_symbol(name, kind, declaration, signature, doc);
+        self.bind(symbol, name);
+    }
+    fn lookup(&self, frame: &Frame, name: &str) -> Option<usize> {
+        frame.scopes.iter().rev().find_map(|scope| {
+            scope
+                .symbols
+                .iter()
+                .rev()
+                .find(|symbol| self.table.symbols[**symbol].name == name)
+                .copied()
+        })
+    }
+    /// the parameters and body of a function, in the order
+    /// [`Compiler::compile_function`](super::compiler::Compiler) compiles them
+    fn parameters_and_body(&mut self, parameters: &mut [Parameter], block: &mut Block) {
+        // every parameter is a local before any default runs
+        for parameter in parameters.iter_mut() {
+            let span = parameter.name.span();
+            self.declare(&mut parameter.name, SymbolKind::Parameter, span, span, None);
+        }
+        for parameter in parameters.iter_mut() {
+            if let Some(default) = &mut parameter.default {
+                self.visit_expression(default);
+            }
+        }
+        self.visit_block(block);
+    }
+    fn function_declaration(&mut self, declaration: &mut FunctionDeclaration, kind: SymbolKind) {
+        let signature = Span::new(
+            declaration.span.start as usize,
+            declaration.block.span.start as usize,
+        );
+        let symbol = self.add_symbol(
+            &declaration.name,
+            kind,
+            declaration.span,
+            signature,
+            declaration.doc.clone(),
+        );
+        // the body is compiled before the name is declared
+        let FunctionDeclaration {
+            parameters, block, ..
+        } = declaration;
+        self.function(Some(symbol), declaration.span, |this| {
+            this.parameters_and_body(parameters, block)
+        });
+        if kind != SymbolKind::Method {
+            self.bind(symbol, &mut declaration.name);
+        }
+    }
+    /// the global declared last before each use, or the first one after it
+    fn resolve_globals(&mut self) {
+        // the declarations of each global name, in the order they're written
+        let mut declared: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, symbol) in self.table.symbols.iter().enumerate() {
+            if symbol.resolution == Resolution::Global {
+                declared.entry(&symbol.name).or_default().push(i);
+            }
+        }
+        for symbols in declared.values_mut() {
+            symbols.sort_by_key(|symbol| self.table.symbols[*symbol].span.start);
+        }
+        for (used, name) in std::mem::take(&mut self.globals) {
+            let Some(symbols) = declared.get(name.as_str()) else {
+                continue;
+            };
+            let start = self.table.uses[used].span.start;
+            let before =
+                symbols.partition_point(|symbol| self.table.symbols[*symbol].span.start <= start);
+            self.table.uses[used].symbol = Some(symbols[before.saturating_sub(1)]);
+        }
+    }
+}
+impl VisitorMut for Resolver<'_, '_> {
+    fn visit_identifier(&mut self, identifier: &mut Identifier) {
+        let span = identifier.span();
+        let frame = self.frames.last().unwrap();
+        if let Some(symbol) = self.lookup(frame, &identifier.lexeme) {
+            identifier.resolution = self.table.symbols[symbol].resolution;
+            self.table.uses.push(Use {
+                span,
+                symbol: Some(symbol),
+            });
+            return;
+        }
+        let captured = self.frames[..self.frames.len() - 1]
+            .iter()
+            .rev()
+            .find_map(|frame| self.lookup(frame, &identifier.lexeme));
+        if let Some(symbol) = captured {
+            self.table.captures.push(Capture { span, symbol });
+            // there are no upvalues, reading it as a global would quietly
+            // give the wrong value
+            self.error(span, "Can't capture a local of an enclosing function");
+        }
+        identifier.resolution = Resolution::Global;
+        self.globals
+            .push((self.table.uses.len(), identifier.lexeme.clone()));
+        self.table.uses.push(Use { span, symbol: None });
+    }
+    fn visit_expression(&mut self, expr: &mut Expression) {
+        match expr {
+            Expression::Tuple(elements, _) | Expression::Array(elements, _) => {
+                for element in elements.iter_mut() {
+                    self.operand(element);
+                }
+                self.consume_operands(elements.len());
+            }
+            _ => visit_mut::walk_expression(self, expr),
+        }
+    }
+    fn visit_binary(&mut self, binary: &mut BinaryExpr) {
+        self.visit_node(&mut binary.lhs);
+        self.frame().slots += 1;
+        self.visit_node(&mut binary.rhs);
+        self.consume_operands(1);
+    }
+    fn visit_comparison(&mut self, comparison: &mut Comparison) {
+        self.operand(&mut comparison.lhs);
+        self.visit_expression(&mut comparison.rhs);
+        self.consume_operands(1);
+    }
+    fn visit_call(&mut self, call: &mut CallExpr) {
+        self.operand(&mut call.callee);
+        for argument in call.parameters.iter_mut() {
+            self.operand(argument);
+        }
+        for (_, argument) in &mut call.named {
+            self.operand(argument);
+        }
+        self.consume_operands(1 + call.parameters.len() + call.named.len());
+    }
+    fn visit_set_property(&mut self, property: &mut SetProperty) {
+        self.operand(&mut property.object);
+        self.visit_expression(&mut property.value);
+        self.consume_operands(1);
+    }
+    fn visit_invoke(&mut self, invoke: &mut Invoke) {
+        self.operand(&mut invoke.object);
+        for argument in &mut invoke.arguments {
+            self.operand(argument);
+        }
+        for (_, argument) in &mut invoke.named {
+            self.operand(argument);
+        }
+        self.consume_operands(1 + invoke.arguments.len() + invoke.named.len());
+    }
+    fn visit_assertion(&mut self, assertion: &mut Assertion) {
+        self.operand(&mut assertion.lhs);
+        let mut count = 1;
+        for operand in [&mut assertion.rhs, &mut assertion.message]
+            .into_iter()
+            .flatten()
+        {
+            self.operand(operand);
+            count += 1;
+        }
+        self.consume_operands(count);
+    }
+    fn visit_block(&mut self, block: &mut Block) {
+        block.locals = self.scoped(block.span, |this| visit_mut::walk_block(this, block));
+    }
+    fn visit_variable_declaration(&mut self, declaration: &mut VariableDeclaration) {
+        // the initializer runs before the names exist
+        self.visit_expression(&mut declaration.intializer);
+        let signature = Span::new(
+            declaration.span.start as usize,
+            declaration.intializer.span().start as usize,
+        );
+        let names = declaration.binding.names();
+        for (i, name) in names.iter().enumerate() {
+            if names[..i]
+                .iter()
+                .any(|earlier| earlier.lexeme == name.lexeme)
+            {
+                self.error(name.span(), "Name is bound twice in the same pattern");
+            }
+        }
+        let span = declaration.span;
+        let doc = declaration.doc.clone();
+        for name in declaration.binding.names_mut() {
+            self.declare(name, SymbolKind::Variable, span, signature, doc.clone());
+        }
+    }
+    fn visit_function_declaration(&mut self, declaration: &mut FunctionDeclaration) {
+        self.function_declaration(declaration, SymbolKind::Function)
+    }
+    fn visit_function(&mut self, function: &mut FunctionExpr) {
+        let FunctionExpr {
+            parameters, block, ..
+        } = function;
+        self.function(None, function.span, |this| {
+            this.parameters_and_body(parameters, block)
+        })
+    }
+    fn visit_test_declaration(&mut self, declaration: &mut TestDeclaration) {
+        if !self.at_top_level() {
+            self.error(
+                declaration.token.span,
+                "Tests can only be declared at the top level of a file",
+            );
+        }
+        let block = &mut declaration.block;
+        self.function(None, declaration.span, |this| this.visit_block(block))
+    }
+    fn visit_class_declaration(&mut self, declaration: &mut ClassDeclaration) {
+        let signature = declaration.span.to(declaration.name.span());
+        let class = self.add_symbol(
+            &declaration.name,
+            SymbolKind::Class,
+            declaration.span,
+            signature,
+            None,
+        );
+        let parent = self.parent.replace(class);
+        for method in &mut declaration.methods {
+            self.function_declaration(method, SymbolKind::Method);
+        }
+        self.parent = parent;
+        self.bind(class, &mut declaration.name);
+    }
+    fn visit_enum_declaration(&mut self, declaration: &mut EnumDeclaration) {
+        let variants = &declaration.variants;
+        for (i, variant) in variants.iter().enumerate() {
+            if variants[..i]
+                .iter()
+                .any(|earlier| earlier.name.lexeme == variant.name.lexeme)
+            {
+                self.error(
+                    variant.name.span(),
+                    "Variant is declared twice in the same enum",
+                );
+            }
+        }
+        let span = declaration.span;
+        self.declare(&mut declaration.name, SymbolKind::Enum, span, span, None);
+    }
+    fn visit_match(&mut self, match_expr: &mut MatchExpr) {
+        // the matched value is kept in a hidden local for the arms to test
+        self.scoped(match_expr.span, |this| {
+            this.visit_expression(&mut match_expr.value);
+            let frame = this.frame();
+            match_expr.slot = frame.slots;
+            frame.slots += 1;
+            if match_expr.slot >= MAX_LOCALS {
+                this.error(match_expr.token.span, "Too many locals in one function");
+            }
+            for arm in &mut match_expr.arms {
+                this.visit_match_arm(arm);
+            }
+        });
+    }
+    fn visit_match_arm(&mut self, arm: &mut MatchArm) {
+        self.scoped(arm.span, |this| visit_mut::walk_match_arm(this, arm));
+    }
+    fn visit_pattern(&mut self, pattern: &mut Pattern) {
+        match pattern {
+            Pattern::Binding(name) => {
+                let span = name.span();
+                self.declare(name, SymbolKind::Variable, span, span, None);
+            }
+            _ => visit_mut::walk_pattern(self, pattern),
+        }
+    }
+    fn visit_try_catch(&mut self, try_catch: &mut TryCatch) {
+        self.visit_block(&mut try_catch.body);
+        let span = try_catch.binding.span().to(try_catch.handler.span);
+        self.scoped(span, |this| {
+            let binding = &mut try_catch.binding;
+            let span = binding.span();
+            this.declare(binding, SymbolKind::Variable, span, span, None);
+            this.visit_block(&mut try_catch.handler);
+        });
+    }
+    fn visit_return(&mut self, return_stmt: &mut ReturnStmt) {
+        if self.at_top_level() {
+            // just the `return` keyword
+            let keyword = Span {
+                start: return_stmt.span.start,
+                len: "return".len() as u32,
+            };
+            self.error(keyword, "Cannot return from the top level of a script");
+        }
+        visit_mut::walk_return(self, return_stmt)
+    }
+}
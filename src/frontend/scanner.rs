@@ -1,77 +1,48 @@
 use std::{char, fmt};
 
-use super::ast::expression::comparison::ComparisonKind;
+use super::{ast::expression::comparison::ComparisonKind, span::Span};
 
 #[derive(Debug, Clone, Default)]
 pub struct Scanner {
     pub source: String,
     pub start: usize,
     pub current: usize,
-    pub line: usize,
-    pub tokens: Vec<Token>,
-    pub line_info: LineInfo,
+    /// why each error token was produced, tokens are `Copy` so they can't
+    /// carry the message themselves
+    pub errors: Vec<(Span, String)>,
+    /// the `//` and `/* */` comments skipped so far, for tools like the
+    /// formatter that have to keep them
+    pub comments: Vec<Span>,
 }
 macro_rules! token {
     ($self:ident, Error, $reason:expr) => {{
+        let span = Span::new($self.start, $self.current);
+        $self.errors.push((span, $reason));
         Token {
             kind: TokenKind::Error,
-            lexeme: $reason,
-            line: $self.line,
-            length: $self.current - $self.start,
-            position: Position {
-                line: $self.line,
-                start_in_line: $self.line_info.start,
-                start_in_source: ($self.start as u16),
-            },
+            span,
         }
     }};
     ($self:ident, Identifier) => {{
         Token {
             kind: $self.identifier_from_lexeme(),
-            lexeme: $self.source[$self.start..$self.current].to_string(),
-            line: $self.line,
-            length: $self.current - $self.start,
-            position: Position {
-                line: $self.line,
-                start_in_line: $self.line_info.start,
-                start_in_source: ($self.start as u16),
-            },
+            span: Span::new($self.start, $self.current),
         }
     }};
     ($self:ident, $kind:ident) => {{
         Token {
             kind: TokenKind::$kind,
-            lexeme: $self.source[$self.start..$self.current].to_string(),
-            line: $self.line,
-            length: $self.current - $self.start,
-            position: Position {
-                line: $self.line,
-                start_in_line: $self.line_info.start,
-                start_in_source: ($self.start as u16),
-            },
+            span: Span::new($self.start, $self.current),
         }
     }};
 }
-#[derive(Clone, PartialEq, Debug, Default)]
+/// a token only knows where it is, [`Scanner::lexeme`] gets its text
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
 pub struct Token {
     pub kind: TokenKind,
-    pub lexeme: String,
-    pub line: usize,
-    pub length: usize,
-    pub position: Position,
+    pub span: Span,
 }
-#[derive(Clone, PartialEq, Debug, Default)]
-pub struct Position {
-    pub line: usize,
-    pub start_in_line: u16,
-    pub start_in_source: u16,
-}
-impl fmt::Display for Token {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Value: {} Kind: {}", self.lexeme, self.kind)
-    }
-}
-#[derive(Copy, Clone, PartialEq, Debug, Default)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
 pub enum TokenKind {
     Identifier,
     Let,
@@ -79,6 +50,7 @@ pub enum TokenKind {
     Use,
     Return,
     Hash,
+    Assert,
     AssertEq,
     AssertNe,
     Print,
@@ -92,6 +64,20 @@ pub enum TokenKind {
     False,
     True,
     Func,
+    Class,
+    SelfKeyword,
+    Dot,
+    DotDot,
+    DotDotEqual,
+    DotDotDot,
+    FatArrow,
+    Match,
+    Enum,
+    Is,
+    Pipe,
+    Try,
+    Catch,
+    Throw,
     RightBracket,
     LeftBracket,
     RightBrace,
@@ -104,6 +90,8 @@ pub enum TokenKind {
 
     Number,
     String,
+    /// `/// text`, the lexeme is the text after the slashes
+    DocComment,
 
     Plus,
     PlusEqual,
@@ -113,6 +101,13 @@ pub enum TokenKind {
     SlashEqual,
     Star,
     StarEqual,
+    /// `~/`, integer division
+    TildeSlash,
+    Percent,
+    Ampersand,
+    Caret,
+    LessLess,
+    GreaterGreater,
     Bang,
     BangEqual,
     Less,
@@ -121,6 +116,7 @@ pub enum TokenKind {
     GreaterEqual,
 
     SemiColon,
+    Colon,
     Comma,
 
     Error,
@@ -134,18 +130,16 @@ impl fmt::Display for TokenKind {
         write!(f, "{:?}", self)
     }
 }
-#[derive(Debug, Clone, PartialEq, Default)]
-pub struct LineInfo {
-    pub current: u16,
-    pub start: u16,
-}
 impl Scanner {
-    pub fn scan_thru(&mut self) {
+    /// every token up to and including the `EOF`
+    pub fn scan_thru(&mut self) -> Vec<Token> {
+        let mut tokens = Vec::new();
         loop {
-            if self.at_end() {
-                break;
+            let token = self.next_token();
+            tokens.push(token);
+            if token.kind == TokenKind::EOF {
+                break tokens;
             }
-            self.next_token();
         }
     }
     pub fn new(source: String) -> Scanner {
@@ -153,44 +147,59 @@ impl Scanner {
             source,
             start: 0,
             current: 0,
-            line: 0,
-            tokens: Vec::new(),
-            line_info: LineInfo {
-                current: 0,
-                start: 0,
-            },
+            errors: Vec::new(),
+            comments: Vec::new(),
         }
     }
     pub fn reset(&mut self, source: String) {
         self.source = source;
         self.start = 0;
         self.current = 0;
-        self.line = 0;
-        self.line_info.current = 0;
-        self.line_info.start = 0;
-        self.tokens.clear()
+        self.errors.clear();
+        self.comments.clear()
+    }
+    /// what a token stands for, the contents of a string without its quotes,
+    /// the text of a doc comment, the reason of an error, or the source
+    /// text for anything else
+    pub fn lexeme(&self, token: &Token) -> &str {
+        let text = &self.source[token.span.range()];
+        match token.kind {
+            TokenKind::String if text.starts_with('r') => {
+                let hashes = text[1..].len() - text[1..].trim_start_matches('#').len();
+                &text[2 + hashes..text.len() - 1 - hashes]
+            }
+            TokenKind::String => &text[1..text.len() - 1],
+            TokenKind::DocComment => text[3..].trim(),
+            TokenKind::Error => self
+                .errors
+                .iter()
+                .rev()
+                .find(|(span, _)| *span == token.span)
+                .map_or("unexpected token", |(_, reason)| reason),
+            _ => text,
+        }
     }
     pub fn next_token(&mut self) -> Token {
-        self.ignore_whitespace();
+        if let Some(error) = self.ignore_whitespace() {
+            return error;
+        }
 
+        self.start = self.current;
         if self.at_end() {
             return token!(self, EOF);
         }
-        self.start = self.current;
-        self.line_info.start = self.line_info.current;
         let char = self.advance();
-        if char.is_alphabetic() {
-            let token = self.identifier();
-            self.tokens.push(token.clone());
-            return token;
+        if char == 'r' && matches!(self.peek_or_nul(), '"' | '#') {
+            return self.raw_string();
+        }
+        if is_identifier_start(char) {
+            return self.identifier();
         }
         if char.is_ascii_digit() {
-            let token = self.number();
-            self.tokens.push(token.clone());
-            return token;
+            return self.number();
         }
 
-        let token = match char {
+        match char {
             '[' => {
                 token!(self, LeftBracket)
             }
@@ -205,9 +214,29 @@ impl Scanner {
                 if self.matches('=') {
                     return token!(self, EqualEqual);
                 }
+                if self.matches('>') {
+                    return token!(self, FatArrow);
+                }
                 token!(self, Equal)
             }
             '*' => token!(self, Star),
+            '%' => token!(self, Percent),
+            '&' => token!(self, Ampersand),
+            '^' => token!(self, Caret),
+            '~' => {
+                if self.matches('/') {
+                    return token!(self, TildeSlash);
+                }
+                token!(self, Error, String::from("unexpected character `~`"))
+            }
+            '/' if self.matches('/') => {
+                // `///`, the doc comment runs until the end of the line
+                self.matches('/');
+                while !self.at_end() && self.peek() != '\n' {
+                    self.advance();
+                }
+                token!(self, DocComment)
+            }
             '/' => {
                 if self.matches('=') {
                     return token!(self, SlashEqual);
@@ -231,12 +260,18 @@ impl Scanner {
                 if self.matches('=') {
                     return token!(self, GreaterEqual);
                 }
+                if self.matches('>') {
+                    return token!(self, GreaterGreater);
+                }
                 token!(self, Greater)
             }
             '<' => {
                 if self.matches('=') {
                     return token!(self, LessEqual);
                 }
+                if self.matches('<') {
+                    return token!(self, LessLess);
+                }
                 token!(self, Less)
             }
             '{' => token!(self, LeftBrace),
@@ -244,6 +279,20 @@ impl Scanner {
             ')' => token!(self, RightParen),
             '}' => token!(self, RightBrace),
             ';' => token!(self, SemiColon),
+            ':' => token!(self, Colon),
+            '.' => {
+                if self.matches('.') {
+                    if self.matches('=') {
+                        return token!(self, DotDotEqual);
+                    }
+                    if self.matches('.') {
+                        return token!(self, DotDotDot);
+                    }
+                    return token!(self, DotDot);
+                }
+                token!(self, Dot)
+            }
+            '|' => token!(self, Pipe),
             ',' => token!(self, Comma),
             '!' => {
                 if self.matches('=') {
@@ -251,110 +300,277 @@ impl Scanner {
                 }
                 token!(self, Bang)
             }
-            _ => token!(self, Error, "unexpected character".to_string()),
-        };
-        self.tokens.push(token.clone());
-        token
+            char => token!(self, Error, format!("unexpected character `{char}`")),
+        }
     }
 }
 impl Scanner {
     fn advance(&mut self) -> char {
-        self.current += 1;
-        self.line_info.current += 1;
-        self.source.as_bytes()[self.current - 1] as char
+        let char = self.peek();
+        self.current += char.len_utf8();
+        char
     }
     fn peek(&self) -> char {
-        self.source.as_bytes()[self.current] as char
+        self.source[self.current..].chars().next().unwrap()
     }
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {
-            '\0'
-        } else {
-            return self.source.as_bytes()[self.current + 1] as char;
-        }
+        let mut chars = self.source[self.current..].chars();
+        chars.next();
+        chars.next().unwrap_or('\0')
     }
     pub fn at_end(&self) -> bool {
         self.current >= self.source.len()
     }
-    fn remaining(&self) -> usize {
-        self.source.len() - self.current
-    }
     fn matches(&mut self, to: char) -> bool {
         if self.at_end() || self.peek() != to {
             false
         } else {
-            self.current += 1;
+            self.advance();
             true
         }
     }
-    fn lexeme(&self) -> &str {
+    fn current_lexeme(&self) -> &str {
         &self.source[self.start..self.current]
     }
-    fn ignore_whitespace(&mut self) {
+    /// skips whitespace and comments, except `///` doc comments which become
+    /// tokens, only fails on an unterminated `/* */`
+    fn ignore_whitespace(&mut self) -> Option<Token> {
         while !self.at_end() {
             let peeked = self.peek();
             match peeked {
-                ' ' | '\t' | '\r' => {
+                ' ' | '\t' | '\r' | '\n' => {
                     self.advance();
                 }
-                '\n' => {
-                    self.line += 1;
-                    self.advance();
-                    self.line_info.current = 0;
-                    self.line_info.start = 0;
-                }
-                '/' => {
-                    if self.peek_next() == '/' {
-                        while !self.at_end() && self.peek() != '\n' {
-                            self.advance();
-                        }
-                    } else {
+                '/' if self.peek_next() == '/' => {
+                    if self.is_doc_comment() {
                         break;
                     }
+                    let start = self.current;
+                    while !self.at_end() && self.peek() != '\n' {
+                        self.advance();
+                    }
+                    self.comments.push(Span::new(start, self.current));
+                }
+                '/' if self.peek_next() == '*' => {
+                    if let Some(error) = self.block_comment() {
+                        return Some(error);
+                    }
                 }
                 _x => {
                     break;
                 }
             }
         }
+        None
+    }
+    /// `///` but not `////`, which is just a line comment
+    fn is_doc_comment(&self) -> bool {
+        let rest = &self.source[self.current..];
+        rest.starts_with("///") && !rest.starts_with("////")
+    }
+    /// `/* ... */`, which can contain other block comments
+    fn block_comment(&mut self) -> Option<Token> {
+        self.start = self.current;
+        let mut depth = 0;
+        loop {
+            if self.at_end() {
+                // just point at the `/*`
+                self.current = self.start + 2;
+                let error = token!(self, Error, String::from("unterminated block comment"));
+                self.current = self.source.len();
+                return Some(error);
+            }
+            match self.advance() {
+                '/' if self.matches('*') => depth += 1,
+                '*' if self.matches('/') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.comments.push(Span::new(self.start, self.current));
+                        return None;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    /// `r"..."` or `r#"..."#`, after the `r`, can span lines and nothing in it
+    /// is escaped, any number of `#`s lets the string contain `"#`
+    fn raw_string(&mut self) -> Token {
+        let mut hashes = 0;
+        while self.matches('#') {
+            hashes += 1;
+        }
+        if !self.matches('"') {
+            return token!(
+                self,
+                Error,
+                String::from("expected '\"' to start a raw string")
+            );
+        }
+        let closing = format!("\"{}", "#".repeat(hashes));
+        loop {
+            if self.at_end() {
+                return token!(self, Error, String::from("unterminated raw string"));
+            }
+            if self.source[self.current..].starts_with(&closing) {
+                break;
+            }
+            self.advance();
+        }
+        self.current += closing.len();
+        token!(self, String)
     }
     fn string(&mut self) -> Token {
         while !self.at_end() && self.peek() != '"' {
-            if self.peek() == '\n' {
-                self.line += 1
-            }
             self.advance();
         }
         if self.at_end() {
             return token!(self, Error, String::from("unterminated string"));
         }
         self.advance();
-        Token {
-            kind: TokenKind::String,
-            lexeme: self.source[self.start + 1..self.current - 1].to_string(),
-            line: self.line,
-            length: self.current - self.start,
-            position: Position {
-                line: self.line,
-                start_in_line: self.line_info.start,
-                start_in_source: self.start as u16,
-            },
-        }
+        token!(self, String)
     }
+    /// `123`, `1_000`, `1.5`, `6.02e23`, or `0x`, `0b` and `0o` followed by
+    /// digits in that base, the first digit has already been consumed
     fn number(&mut self) -> Token {
-        while !self.at_end() && self.peek().is_ascii_digit() {
+        let first = self.source.as_bytes()[self.start] as char;
+        let radix = match (first, self.peek_or_nul()) {
+            ('0', 'x') => Some((16, "hexadecimal")),
+            ('0', 'b') => Some((2, "binary")),
+            ('0', 'o') => Some((8, "octal")),
+            _ => None,
+        };
+        if let Some((radix, name)) = radix {
             self.advance();
+            let digits_start = self.current;
+            self.eat_alphanumeric();
+            let digits = &self.source[digits_start..self.current];
+            let reason = if digits.chars().all(|char| char == '_') {
+                Some(format!("{name} literal has no digits"))
+            } else if let Some(digit) = digits
+                .chars()
+                .find(|char| *char != '_' && !char.is_digit(radix))
+            {
+                Some(format!("`{digit}` is not a valid {name} digit"))
+            } else if digits.ends_with('_') {
+                Some(String::from("a number can't end with `_`"))
+            } else {
+                None
+            };
+            if reason.is_none() && self.dot_in_number() {
+                self.skip_fraction();
+                return token!(
+                    self,
+                    Error,
+                    format!("a {name} literal can't have a fraction")
+                );
+            }
+            return match reason {
+                Some(reason) => token!(self, Error, reason),
+                None => token!(self, Number),
+            };
         }
-        if self.remaining() >= 2 && self.peek() == '.' && self.peek_next().is_ascii_digit() {
+
+        if let Err(reason) = self.digits() {
+            return token!(self, Error, reason);
+        }
+        if self.dot_in_number() {
+            if !self.peek_next().is_ascii_digit() {
+                self.skip_fraction();
+                return token!(self, Error, String::from("expected digits after the `.`"));
+            }
+            self.advance();
+            if let Err(reason) = self.digits() {
+                return token!(self, Error, reason);
+            }
+            if self.dot_in_number() {
+                while self.dot_in_number() {
+                    self.skip_fraction();
+                }
+                return token!(self, Error, String::from("a number can only have one `.`"));
+            }
+        }
+        if matches!(self.peek_or_nul(), 'e' | 'E') {
             self.advance();
-            while !self.at_end() && self.peek().is_ascii_digit() {
+            if matches!(self.peek_or_nul(), '+' | '-') {
                 self.advance();
             }
+            if !self.peek_or_nul().is_ascii_digit() {
+                self.eat_alphanumeric();
+                return token!(self, Error, String::from("exponent has no digits"));
+            }
+            if let Err(reason) = self.digits() {
+                return token!(self, Error, reason);
+            }
+        }
+        // `123abc` is one bad token rather than a number and a name
+        if is_identifier_continue(self.peek_or_nul()) {
+            let suffix_start = self.current;
+            self.eat_alphanumeric();
+            let suffix = self.source[suffix_start..self.current].to_string();
+            return token!(
+                self,
+                Error,
+                format!("invalid suffix `{suffix}` on a number")
+            );
+        }
+        let lexeme = self.current_lexeme();
+        if lexeme.contains(['.', 'e', 'E']) {
+            let number = lexeme.replace('_', "").parse::<f64>();
+            if number.is_ok_and(f64::is_infinite) {
+                return token!(self, Error, String::from("number literal is too large"));
+            }
         }
         token!(self, Number)
     }
+    /// whether a `.` belonging to the number comes next, as in `1.5` or
+    /// `1.e5`, rather than a field access
+    fn dot_in_number(&self) -> bool {
+        let mut rest = self.source[self.current..].chars();
+        if rest.next() != Some('.') {
+            return false;
+        }
+        match rest.next() {
+            Some(char) if char.is_ascii_digit() => true,
+            Some('e' | 'E') => matches!(rest.next(), Some('0'..='9' | '+' | '-')),
+            _ => false,
+        }
+    }
+    /// skips a `.` and what follows it, so a malformed number is one token
+    fn skip_fraction(&mut self) {
+        self.advance();
+        if matches!(self.peek_or_nul(), 'e' | 'E') {
+            self.advance();
+            if matches!(self.peek_or_nul(), '+' | '-') {
+                self.advance();
+            }
+        }
+        self.eat_alphanumeric();
+    }
+    /// digits and `_` separators, which can't end the run
+    fn digits(&mut self) -> Result<(), String> {
+        while self.peek_or_nul().is_ascii_digit() || self.peek_or_nul() == '_' {
+            self.advance();
+        }
+        if self.source.as_bytes()[self.current - 1] == b'_' {
+            return Err(String::from("a number can't end with `_`"));
+        }
+        Ok(())
+    }
+    fn eat_alphanumeric(&mut self) {
+        while is_identifier_continue(self.peek_or_nul()) {
+            self.advance();
+        }
+    }
+    fn peek_or_nul(&self) -> char {
+        if self.at_end() {
+            '\0'
+        } else {
+            self.peek()
+        }
+    }
     fn identifier_from_lexeme(&mut self) -> TokenKind {
-        match self.lexeme() {
+        match self.current_lexeme() {
             "let" => TokenKind::Let,
             "mut" => TokenKind::Mut,
             "use" => TokenKind::Use,
@@ -368,24 +584,38 @@ impl Scanner {
             "false" => TokenKind::False,
             "true" => TokenKind::True,
             "func" => TokenKind::Func,
+            "class" => TokenKind::Class,
+            "self" => TokenKind::SelfKeyword,
+            "try" => TokenKind::Try,
+            "match" => TokenKind::Match,
+            "enum" => TokenKind::Enum,
+            "is" => TokenKind::Is,
+            "catch" => TokenKind::Catch,
+            "throw" => TokenKind::Throw,
             "return" => TokenKind::Return,
             "print" => TokenKind::Print,
+            "assert" => TokenKind::Assert,
             "assert_eq" => TokenKind::AssertEq,
             "assert_ne" => TokenKind::AssertNe,
             _ => TokenKind::Identifier,
         }
     }
     fn identifier(&mut self) -> Token {
-        while !self.at_end() && {
-            let char = self.peek();
-            char.is_alphanumeric() || char == '_'
-        } {
+        while !self.at_end() && is_identifier_continue(self.peek()) {
             self.advance();
         }
         token!(self, Identifier)
     }
 }
 
+/// identifiers follow Unicode's XID rules, plus `_` which is allowed anywhere
+fn is_identifier_start(char: char) -> bool {
+    char == '_' || unicode_ident::is_xid_start(char)
+}
+fn is_identifier_continue(char: char) -> bool {
+    unicode_ident::is_xid_continue(char)
+}
+
 impl TryFrom<TokenKind> for ComparisonKind {
     type Error = String;
 
@@ -397,6 +627,7 @@ impl TryFrom<TokenKind> for ComparisonKind {
             TokenKind::LessEqual => Ok(ComparisonKind::LessEq),
             TokenKind::EqualEqual => Ok(ComparisonKind::Equal),
             TokenKind::BangEqual => Ok(ComparisonKind::NotEqual),
+            TokenKind::Is => Ok(ComparisonKind::Is),
             x => Err(format!("cannot convert {} to comparison kind", x)),
         }
     }
@@ -0,0 +1,82 @@
+use std::ops::Range;
+
+/// a range of bytes in the source, `u32` so scripts up to 4 GiB work while
+/// tokens stay small
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Span {
+    pub start: u32,
+    pub len: u32,
+}
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span {
+            start: start as u32,
+            len: (end - start) as u32,
+        }
+    }
+    pub fn end(&self) -> usize {
+        (self.start + self.len) as usize
+    }
+    pub fn range(&self) -> Range<usize> {
+        self.start as usize..self.end()
+    }
+    /// from the start of `self` to the end of `other`
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start as usize, other.end().max(self.start as usize))
+    }
+}
+
+/// a line and a column, both starting at 0, columns count characters
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// where every line of a source starts, tokens only keep byte offsets and
+/// lines and columns are worked out from this when something asks for them
+#[derive(Clone, PartialEq, Debug)]
+pub struct LineIndex {
+    line_starts: Vec<u32>,
+    source: String,
+}
+impl Default for LineIndex {
+    fn default() -> Self {
+        LineIndex::new("")
+    }
+}
+impl LineIndex {
+    pub fn new(source: &str) -> LineIndex {
+        let line_starts = std::iter::once(0)
+            .chain(
+                source
+                    .bytes()
+                    .enumerate()
+                    .filter(|(_, byte)| *byte == b'\n')
+                    .map(|(offset, _)| offset as u32 + 1),
+            )
+            .collect();
+        LineIndex {
+            line_starts,
+            source: source.to_owned(),
+        }
+    }
+    pub fn position(&self, offset: u32) -> Position {
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let line_start = self.line_starts[line] as usize;
+        let offset = (offset as usize).min(self.source.len());
+        let column = self
+            .source
+            .get(line_start..offset)
+            .map_or(0, |text| text.chars().count());
+        Position { line, column }
+    }
+    /// the offset a line starts at, `None` past the last line
+    pub fn line_start(&self, line: usize) -> Option<u32> {
+        self.line_starts.get(line).copied()
+    }
+    /// the source text a span covers
+    pub fn text(&self, span: Span) -> &str {
+        self.source.get(span.range()).unwrap_or_default()
+    }
+}
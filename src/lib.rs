@@ -2,4 +2,7 @@
 pub mod backend;
 pub mod cli_helper;
 pub mod common;
+pub mod formatter;
 pub mod frontend;
+pub mod lsp;
+pub mod test_runner;
@@ -0,0 +1,187 @@
+//! what the language server knows about one document, worked out again on
+//! every change: what the parser reported, the resolver's symbol table and
+//! the tokens to highlight
+
+use std::{cell::RefCell, collections::HashMap, path::Path, rc::Rc};
+
+use crate::{
+    cli_helper::{Diagnostics, Report},
+    frontend::{
+        compiler::{directives::Directives, FunctionType},
+        parser::Parser,
+        resolver::{Resolver, Symbol, SymbolKind, SymbolTable},
+        scanner::{Scanner, Token, TokenKind},
+        span::{LineIndex, Span},
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    pub source: String,
+    pub lines: LineIndex,
+    /// what the parser, the lints and the resolver reported
+    pub reports: Vec<Report>,
+    pub table: SymbolTable,
+    /// the symbol each name in the file refers to, by where the name starts,
+    /// and whether it's declared there
+    pub names: HashMap<u32, (usize, bool)>,
+    /// the tokens of the file without the `EOF`
+    pub tokens: Vec<Token>,
+    /// the `//` and `/* */` comments, which aren't tokens
+    pub comments: Vec<Span>,
+}
+impl Analysis {
+    pub fn new(path: &Path, source: &str) -> Analysis {
+        let diagnostics = Rc::new(RefCell::new(Diagnostics::new(path)));
+        diagnostics.borrow_mut().quiet = true;
+        let mut parser = Parser::new(
+            Scanner::new(source.to_owned()),
+            diagnostics.clone(),
+            FunctionType::Script,
+            Directives::builtin(),
+        );
+        let mut nodes = parser.parse_file().nodes;
+        let table = Resolver::run(&mut nodes, &diagnostics).table;
+        let reports = std::mem::take(&mut diagnostics.borrow_mut().reports);
+
+        let mut names = HashMap::new();
+        for (index, symbol) in table.symbols.iter().enumerate() {
+            names.insert(symbol.span.start, (index, true));
+        }
+        for used in &table.uses {
+            if let Some(symbol) = used.symbol {
+                names.entry(used.span.start).or_insert((symbol, false));
+            }
+        }
+
+        let mut scanner = Scanner::new(source.to_owned());
+        let mut tokens = scanner.scan_thru();
+        tokens.pop();
+
+        Analysis {
+            source: source.to_owned(),
+            lines: LineIndex::new(source),
+            reports,
+            table,
+            names,
+            tokens,
+            comments: scanner.comments,
+        }
+    }
+    /// how a symbol is declared, `func add(a, b)` or `let x`
+    pub fn detail(&self, symbol: &Symbol) -> String {
+        let text = self.lines.text(symbol.signature);
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        text.trim_end_matches('=').trim_end().to_owned()
+    }
+}
+
+/// how a token is highlighted, the position in [`TOKEN_TYPES`]
+pub fn token_type(analysis: &Analysis, index: usize) -> Option<usize> {
+    let token = analysis.tokens[index];
+    let previous = index
+        .checked_sub(1)
+        .map(|index| analysis.tokens[index].kind);
+    let name = match token.kind {
+        TokenKind::Identifier => {
+            match analysis.names.get(&token.span.start) {
+                Some(&(symbol, _)) => match analysis.table.symbols[symbol].kind {
+                    SymbolKind::Variable => "variable",
+                    SymbolKind::Parameter => "parameter",
+                    SymbolKind::Function => "function",
+                    SymbolKind::Class => "class",
+                    SymbolKind::Method => "method",
+                    SymbolKind::Enum => "enum",
+                },
+                None if previous == Some(TokenKind::Dot) => "property",
+                None if previous == Some(TokenKind::Hash) => "macro",
+                // `test` is only a keyword in front of the test's name
+                None if analysis.lines.text(token.span) == "test"
+                    && analysis.tokens.get(index + 1).map(|token| token.kind)
+                        == Some(TokenKind::String) =>
+                {
+                    "keyword"
+                }
+                None => "variable",
+            }
+        }
+        TokenKind::Let
+        | TokenKind::Mut
+        | TokenKind::Use
+        | TokenKind::Return
+        | TokenKind::Assert
+        | TokenKind::AssertEq
+        | TokenKind::AssertNe
+        | TokenKind::Print
+        | TokenKind::And
+        | TokenKind::Or
+        | TokenKind::If
+        | TokenKind::Else
+        | TokenKind::Nil
+        | TokenKind::While
+        | TokenKind::For
+        | TokenKind::False
+        | TokenKind::True
+        | TokenKind::Func
+        | TokenKind::Class
+        | TokenKind::SelfKeyword
+        | TokenKind::Match
+        | TokenKind::Enum
+        | TokenKind::Is
+        | TokenKind::Try
+        | TokenKind::Catch
+        | TokenKind::Throw => "keyword",
+        TokenKind::Number => "number",
+        TokenKind::String => "string",
+        TokenKind::DocComment => "comment",
+        TokenKind::Hash => "macro",
+        TokenKind::DotDot
+        | TokenKind::DotDotEqual
+        | TokenKind::DotDotDot
+        | TokenKind::FatArrow
+        | TokenKind::Pipe
+        | TokenKind::Equal
+        | TokenKind::EqualEqual
+        | TokenKind::Plus
+        | TokenKind::PlusEqual
+        | TokenKind::Dash
+        | TokenKind::DashEqual
+        | TokenKind::Slash
+        | TokenKind::SlashEqual
+        | TokenKind::Star
+        | TokenKind::StarEqual
+        | TokenKind::TildeSlash
+        | TokenKind::Percent
+        | TokenKind::Ampersand
+        | TokenKind::Caret
+        | TokenKind::LessLess
+        | TokenKind::GreaterGreater
+        | TokenKind::Bang
+        | TokenKind::BangEqual
+        | TokenKind::Less
+        | TokenKind::LessEqual
+        | TokenKind::Greater
+        | TokenKind::GreaterEqual => "operator",
+        _ => return None,
+    };
+    TOKEN_TYPES.iter().position(|kind| *kind == name)
+}
+
+/// the semantic token types the server uses, in the order of its legend
+pub const TOKEN_TYPES: [&str; 13] = [
+    "keyword",
+    "string",
+    "number",
+    "comment",
+    "operator",
+    "variable",
+    "parameter",
+    "function",
+    "method",
+    "class",
+    "enum",
+    "property",
+    "macro",
+];
+/// the one modifier, set on names where they're declared
+pub const TOKEN_MODIFIERS: [&str; 1] = ["declaration"];
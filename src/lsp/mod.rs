@@ -0,0 +1,390 @@
+//! `limesherbet lsp`, a language server speaking json-rpc over stdio
+//!
+//! every change to a document parses it again with the same [`Parser`] the
+//! compiler uses, publishes whatever was reported and keeps an [`Analysis`]
+//! around to answer requests about the document until the next change
+//!
+//! [`Parser`]: crate::frontend::parser::Parser
+
+pub mod analysis;
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+use serde_json::{json, Value};
+
+use crate::frontend::{
+    resolver::{Resolution, SymbolKind},
+    span::Span,
+};
+
+use analysis::{token_type, Analysis, TOKEN_MODIFIERS, TOKEN_TYPES};
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const PARSE_ERROR: i64 = -32700;
+
+#[derive(Debug, Default)]
+pub struct Server {
+    /// the open documents by uri
+    documents: HashMap<String, Analysis>,
+    /// `shutdown` was asked for, `exit` should succeed
+    pub shut_down: bool,
+    /// `exit` was received, nothing else gets handled
+    pub exited: bool,
+}
+impl Server {
+    pub fn new() -> Server {
+        Server::default()
+    }
+    /// handles one message from the client, returning the responses and
+    /// notifications to send back
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let id = message.get("id").cloned();
+        let Some(method) = message["method"].as_str() else {
+            // a response to something the server never asks
+            return Vec::new();
+        };
+        let params = &message["params"];
+        let mut out = Vec::new();
+        let result = match method {
+            "initialize" => Some(Ok(capabilities())),
+            "shutdown" => {
+                self.shut_down = true;
+                Some(Ok(Value::Null))
+            }
+            "exit" => {
+                self.exited = true;
+                None
+            }
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                let uri = document["uri"].as_str().unwrap_or_default();
+                let text = document["text"].as_str().unwrap_or_default();
+                out.push(self.update(uri, text));
+                None
+            }
+            "textDocument/didChange" => {
+                // the server asks for full syncs, the last change is the whole text
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                if let Some(text) = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                {
+                    out.push(self.update(uri, text));
+                }
+                None
+            }
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                self.documents.remove(uri);
+                out.push(publish(uri, Vec::new()));
+                None
+            }
+            "textDocument/semanticTokens/full" => Some(self.document(params).map(semantic_tokens)),
+            "textDocument/documentSymbol" => Some(
+                self.document(params)
+                    .map(|analysis| document_symbols(analysis, None)),
+            ),
+            "textDocument/definition" => Some(self.symbol(params).map(|found| match found {
+                Some((analysis, symbol)) => json!({
+                    "uri": params["textDocument"]["uri"],
+                    "range": range(analysis, analysis.table.symbols[symbol].span),
+                }),
+                None => Value::Null,
+            })),
+            "textDocument/hover" => Some(self.symbol(params).map(|found| match found {
+                Some((analysis, symbol)) => hover(analysis, symbol),
+                None => Value::Null,
+            })),
+            _ if id.is_some() => Some(Err((
+                METHOD_NOT_FOUND,
+                format!("`{method}` isn't supported"),
+            ))),
+            // notifications the server doesn't care about, like `initialized`
+            _ => None,
+        };
+        if let (Some(id), Some(result)) = (id, result) {
+            out.push(match result {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                Err((code, message)) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": code, "message": message },
+                }),
+            });
+        }
+        out
+    }
+    /// analyses the new text and returns the diagnostics to publish for it
+    fn update(&mut self, uri: &str, text: &str) -> Value {
+        let path = Path::new(uri.strip_prefix("file://").unwrap_or(uri));
+        let analysis = Analysis::new(path, text);
+        let diagnostics = analysis
+            .reports
+            .iter()
+            .map(|report| {
+                let span = report.span.unwrap_or_default();
+                json!({
+                    "range": range(&analysis, span),
+                    // error and warning
+                    "severity": if report.warning { 2 } else { 1 },
+                    "source": "limesherbet",
+                    "message": report.message,
+                })
+            })
+            .collect();
+        self.documents.insert(uri.to_owned(), analysis);
+        publish(uri, diagnostics)
+    }
+    fn document(&self, params: &Value) -> Result<&Analysis, (i64, String)> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        self.documents
+            .get(uri)
+            .ok_or_else(|| (INVALID_PARAMS, format!("`{uri}` isn't open")))
+    }
+    /// the symbol at the request's position, if there's one
+    fn symbol(&self, params: &Value) -> Result<Option<(&Analysis, usize)>, (i64, String)> {
+        let analysis = self.document(params)?;
+        let position = &params["position"];
+        let (Some(line), Some(character)) =
+            (position["line"].as_u64(), position["character"].as_u64())
+        else {
+            return Err((INVALID_PARAMS, String::from("expected a position")));
+        };
+        let offset = offset(analysis, line as usize, character as usize);
+        Ok(analysis
+            .table
+            .symbol_at(offset)
+            .map(|symbol| (analysis, symbol)))
+    }
+}
+
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            // full
+            "textDocumentSync": 1,
+            "hoverProvider": true,
+            "definitionProvider": true,
+            "documentSymbolProvider": true,
+            "semanticTokensProvider": {
+                "legend": {
+                    "tokenTypes": TOKEN_TYPES,
+                    "tokenModifiers": TOKEN_MODIFIERS,
+                },
+                "full": true,
+            },
+        },
+        "serverInfo": { "name": "limesherbet", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+fn publish(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+/// lsp positions count utf-16 code units from the start of the line
+fn position(analysis: &Analysis, offset: u32) -> (usize, usize) {
+    let line = analysis.lines.position(offset).line;
+    let start = analysis.lines.line_start(line).unwrap_or_default();
+    let text = analysis
+        .lines
+        .text(Span::new(start as usize, offset as usize));
+    (line, text.encode_utf16().count())
+}
+fn offset(analysis: &Analysis, line: usize, character: usize) -> u32 {
+    let Some(start) = analysis.lines.line_start(line) else {
+        return analysis.source.len() as u32;
+    };
+    let mut units = 0;
+    let mut offset = start as usize;
+    for char in analysis.source[offset..].chars() {
+        if units >= character || char == '\n' {
+            break;
+        }
+        units += char.len_utf16();
+        offset += char.len_utf8();
+    }
+    offset as u32
+}
+fn range(analysis: &Analysis, span: Span) -> Value {
+    let (start_line, start) = position(analysis, span.start);
+    let (end_line, end) = position(analysis, span.end() as u32);
+    json!({
+        "start": { "line": start_line, "character": start },
+        "end": { "line": end_line, "character": end },
+    })
+}
+
+/// every highlighted token as five numbers, the line and start relative to
+/// the previous token, its length, type and modifiers
+fn semantic_tokens(analysis: &Analysis) -> Value {
+    let mut tokens: Vec<(Span, usize, u32)> = (0..analysis.tokens.len())
+        .filter_map(|index| {
+            let span = analysis.tokens[index].span;
+            let modifiers = analysis
+                .names
+                .get(&span.start)
+                .is_some_and(|(_, declared)| *declared) as u32;
+            token_type(analysis, index).map(|kind| (span, kind, modifiers))
+        })
+        .collect();
+    let comment = TOKEN_TYPES
+        .iter()
+        .position(|kind| *kind == "comment")
+        .unwrap();
+    tokens.extend(analysis.comments.iter().map(|span| (*span, comment, 0)));
+    tokens.sort_by_key(|(span, ..)| span.start);
+
+    let mut data = Vec::new();
+    let (mut last_line, mut last_start) = (0, 0);
+    for (span, kind, modifiers) in tokens {
+        // clients don't have to support tokens over several lines, block
+        // comments and raw strings are split up
+        let mut start = span.start as usize;
+        while start < span.end() {
+            let end = analysis.source[start..span.end()]
+                .find('\n')
+                .map_or(span.end(), |newline| start + newline);
+            let (line, character) = position(analysis, start as u32);
+            let length = analysis.source[start..end].encode_utf16().count();
+            if length > 0 {
+                let delta_start = if line == last_line {
+                    character - last_start
+                } else {
+                    character
+                };
+                data.extend([
+                    line - last_line,
+                    delta_start,
+                    length,
+                    kind,
+                    modifiers as usize,
+                ]);
+                (last_line, last_start) = (line, character);
+            }
+            start = end + 1;
+        }
+    }
+    json!({ "data": data })
+}
+
+/// the `func`, `let`, `class` and `enum` declarations inside `parent`,
+/// nested the way they were written
+fn document_symbols(analysis: &Analysis, parent: Option<usize>) -> Value {
+    analysis
+        .table
+        .symbols
+        .iter()
+        .enumerate()
+        .filter(|(_, symbol)| symbol.parent == parent && symbol.kind != SymbolKind::Parameter)
+        .map(|(index, symbol)| {
+            let kind = match symbol.kind {
+                SymbolKind::Class => 5,
+                SymbolKind::Method => 6,
+                SymbolKind::Enum => 10,
+                SymbolKind::Function => 12,
+                SymbolKind::Variable | SymbolKind::Parameter => 13,
+            };
+            json!({
+                "name": symbol.name,
+                "detail": analysis.detail(symbol),
+                "kind": kind,
+                "range": range(analysis, symbol.declaration),
+                "selectionRange": range(analysis, symbol.span),
+                "children": document_symbols(analysis, Some(index)),
+            })
+        })
+        .collect()
+}
+
+fn hover(analysis: &Analysis, symbol: usize) -> Value {
+    let symbol = &analysis.table.symbols[symbol];
+    let what = match symbol.kind {
+        SymbolKind::Parameter => "parameter",
+        _ if symbol.resolution == Resolution::Global => "global",
+        SymbolKind::Method => "method",
+        _ => "local",
+    };
+    let detail = analysis.detail(symbol);
+    let mut value = format!("```limesherbet\n{detail}\n```\n{what}");
+    if let Some(doc) = &symbol.doc {
+        value.push_str("\n\n---\n\n");
+        value.push_str(doc);
+    }
+    json!({
+        "contents": { "kind": "markdown", "value": value },
+        "range": range(analysis, symbol.span),
+    })
+}
+
+/// the next message, `None` once the input is closed, a body that isn't
+/// json is an `InvalidData` error
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let Some(length) = length else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message without a Content-Length",
+        ));
+    };
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+pub fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    output.flush()
+}
+
+/// answers messages until `exit` or until the input is closed, returns
+/// whether the client shut the server down properly first
+pub fn serve(mut input: impl BufRead, mut output: impl Write) -> io::Result<bool> {
+    let mut server = Server::new();
+    loop {
+        let replies = match read_message(&mut input) {
+            Ok(Some(message)) => server.handle(&message),
+            Ok(None) => break,
+            // the whole message was read, the next one can still be fine
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => vec![json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": PARSE_ERROR, "message": err.to_string() },
+            })],
+            Err(err) => return Err(err),
+        };
+        for reply in replies {
+            write_message(&mut output, &reply)?;
+        }
+        if server.exited {
+            break;
+        }
+    }
+    Ok(server.shut_down)
+}
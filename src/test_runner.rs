@@ -0,0 +1,121 @@
+//! runs the `test "name" { ... }` blocks of a file, each one in its own vm
+//! that first runs the file's top level again
+use std::{
+    cell::RefCell,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    backend::vm::{output::Output, VirtualMachine},
+    cli_helper::Diagnostics,
+    common::{function::Function, interner::StringInterner, value::Value},
+    frontend::compiler::{Compiler, FunctionType},
+};
+
+#[derive(Debug)]
+pub struct TestOutcome {
+    pub name: String,
+    pub line: usize,
+    /// why the test failed, `None` if it passed
+    pub failure: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct TestReport {
+    pub path: PathBuf,
+    pub outcomes: Vec<TestOutcome>,
+}
+
+impl TestReport {
+    pub fn passed(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.failure.is_none())
+            .count()
+    }
+    pub fn failed(&self) -> impl Iterator<Item = &TestOutcome> {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.failure.is_some())
+    }
+}
+
+/// every `.mng` file under `path`, or just `path` if it is a file
+pub fn discover(path: &Path) -> Vec<PathBuf> {
+    if !path.is_dir() {
+        return vec![path.to_owned()];
+    }
+    let mut files = Vec::new();
+    let mut entries: Vec<PathBuf> = path
+        .read_dir()
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            files.extend(discover(&entry));
+        } else if entry.extension().and_then(|extension| extension.to_str()) == Some("mng") {
+            files.push(entry);
+        }
+    }
+    files
+}
+
+/// compiles the file, runs its top level once and then every test in isolation,
+/// errors if the file itself can't be compiled or run
+pub fn run_file(path: &Path) -> Result<TestReport, String> {
+    let source = read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let diagnostics = Rc::new(RefCell::new(Diagnostics::new(path)));
+    let interner = Rc::new(RefCell::new(StringInterner::default()));
+    let compiler = Compiler::new(interner.clone(), diagnostics, FunctionType::Script);
+    let (script, tests) = compiler
+        .compile_tests(source)
+        .map_err(|_| format!("{} failed to compile", path.display()))?;
+    if tests.is_empty() {
+        return Ok(TestReport {
+            path: path.to_owned(),
+            outcomes: Vec::new(),
+        });
+    }
+
+    let interner = interner.borrow().clone();
+    let mut vm = VirtualMachine::new(interner.clone());
+    run_function(&mut vm, &script)
+        .map_err(|err| format!("{} failed before running its tests: {err}", path.display()))?;
+
+    // the globals can hold instances a test changes in place, so every test
+    // gets them from its own run of the top level, with the prints dropped
+    let outcomes = tests
+        .iter()
+        .map(|test| {
+            let mut vm = VirtualMachine::new(interner.clone());
+            vm.output = Output::captured().0;
+            let failure = run_function(&mut vm, &script)
+                .and_then(|()| {
+                    vm.output = Output::Stdout;
+                    run_function(&mut vm, &test.function)
+                })
+                .err();
+            TestOutcome {
+                name: test.name.clone(),
+                line: test.line,
+                failure,
+            }
+        })
+        .collect();
+    Ok(TestReport {
+        path: path.to_owned(),
+        outcomes,
+    })
+}
+
+/// runs the function on a fresh stack
+fn run_function(vm: &mut VirtualMachine, function: &Function) -> Result<(), String> {
+    vm.stack.push(Value::Void);
+    vm.call(function, 0)
+        .and_then(|()| vm.run())
+        .map_err(|err| err.to_string())
+}
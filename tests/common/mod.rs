@@ -0,0 +1,220 @@
+//! runs a single script from `tests/scripts` and compares what it did against
+//! what the script (or the files next to it) say it should do
+//!
+//! for a script `x.mng` the expectations are, all optional:
+//! - `x.stdout`, every line `print`ed, compared exactly
+//! - `x.error` (or `x.stderr`), each line has to show up in one of the compile
+//!   or runtime errors
+//! - `// expect: <line>` comments, appended to the expected stdout
+//! - `// expect error: <text>` comments, appended to the expected errors
+//! - `// expect warning: <text>` comments, each has to show up in a warning
+//!
+//! a script without any error (or warning) expectations fails if it reports one
+use std::{
+    any::Any,
+    cell::RefCell,
+    fmt::Write,
+    fs::read_to_string,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    rc::Rc,
+};
+
+use limesherbet::{
+    backend::vm::{output::Output, VirtualMachine},
+    cli_helper::Diagnostics,
+    common::{interner::StringInterner, value::Value},
+    frontend::{
+        ast::node::Node,
+        compiler::{directives::Directives, Compiler, FunctionType},
+        parser::Parser,
+        scanner::Scanner,
+    },
+};
+
+/// parses `source` as a script, the parser is returned to check what it reported
+pub fn parse(source: &str) -> (Parser<'static>, Vec<Node>) {
+    let mut parser = Parser::new(
+        Scanner::new(source.to_string()),
+        Rc::new(RefCell::new(Diagnostics::default())),
+        FunctionType::Script,
+        Directives::builtin(),
+    );
+    let nodes = parser.parse_file().nodes;
+    (parser, nodes)
+}
+
+#[derive(Debug, Default)]
+pub struct Expectations {
+    pub stdout: Option<Vec<String>>,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl Expectations {
+    pub fn load(path: &Path, source: &str) -> Expectations {
+        let mut expectations = Expectations::default();
+        if let Ok(stdout) = read_to_string(path.with_extension("stdout")) {
+            expectations.stdout = Some(stdout.lines().map(str::to_owned).collect());
+        }
+        for extension in ["error", "stderr"] {
+            if let Ok(errors) = read_to_string(path.with_extension(extension)) {
+                expectations.errors.extend(
+                    errors
+                        .lines()
+                        .map(str::trim)
+                        .filter(|line| !line.is_empty())
+                        .map(str::to_owned),
+                );
+            }
+        }
+        for line in source.lines() {
+            // the first `//` can be inside a string or be integer division
+            let Some(comment) = line
+                .match_indices("//")
+                .map(|(i, _)| line[i + 2..].trim())
+                .find(|comment| comment.starts_with("expect"))
+            else {
+                continue;
+            };
+            if let Some(error) = comment.strip_prefix("expect error:") {
+                expectations.errors.push(error.trim().to_owned());
+            } else if let Some(warning) = comment.strip_prefix("expect warning:") {
+                expectations.warnings.push(warning.trim().to_owned());
+            } else if let Some(printed) = comment.strip_prefix("expect:") {
+                expectations
+                    .stdout
+                    .get_or_insert_with(Vec::new)
+                    .push(printed.trim().to_owned());
+            }
+        }
+        expectations
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Outcome {
+    pub stdout: String,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl Outcome {
+    pub fn run(path: &Path, source: String) -> Outcome {
+        let diagnostics = Rc::new(RefCell::new(Diagnostics::new(path)));
+        let interner = Rc::new(RefCell::new(StringInterner::default()));
+        let (output, stdout) = Output::captured();
+
+        let mut errors = Vec::new();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let compiler =
+                Compiler::new(interner.clone(), diagnostics.clone(), FunctionType::Script);
+            let Ok(compiled) = compiler.compile(source) else {
+                return;
+            };
+            let mut vm = VirtualMachine::new(interner.borrow().clone());
+            vm.output = output;
+            vm.stack.push(Value::Void);
+            if let Err(err) = vm.call(&compiled, 0).and_then(|()| vm.run()) {
+                errors.push(err.to_string());
+            }
+        }));
+
+        errors.splice(0..0, diagnostics.borrow().logged.iter().cloned());
+        if let Err(payload) = result {
+            errors.push(panic_message(payload));
+        }
+        let stdout = stdout.borrow().clone();
+        let warnings = diagnostics.borrow().warnings.clone();
+        Outcome {
+            stdout,
+            errors,
+            warnings,
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else {
+        String::from("<panic without a message>")
+    }
+}
+
+/// a line by line diff, `-` is what was expected and `+` is what we got
+fn diff(expected: &[String], actual: &[&str]) -> String {
+    let mut out = String::new();
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(expected), Some(actual)) if expected == actual => {
+                writeln!(out, "    {expected}").unwrap()
+            }
+            (expected, actual) => {
+                if let Some(expected) = expected {
+                    writeln!(out, "  - {expected}").unwrap();
+                }
+                if let Some(actual) = actual {
+                    writeln!(out, "  + {actual}").unwrap();
+                }
+            }
+        }
+    }
+    out
+}
+
+/// every expected line has to be part of something that was reported, and
+/// nothing may be reported when nothing was expected
+fn compare_reports(failures: &mut String, kind: &str, expected: &[String], reported: &[String]) {
+    if expected.is_empty() {
+        if !reported.is_empty() {
+            writeln!(failures, "unexpected {kind}:").unwrap();
+            for report in reported {
+                writeln!(failures, "  + {report}").unwrap();
+            }
+        }
+        return;
+    }
+    let missing: Vec<&String> = expected
+        .iter()
+        .filter(|expected| !reported.iter().any(|report| report.contains(*expected)))
+        .collect();
+    if !missing.is_empty() {
+        writeln!(failures, "expected {kind} were not reported:").unwrap();
+        for report in missing {
+            writeln!(failures, "  - {report}").unwrap();
+        }
+        for report in reported {
+            writeln!(failures, "  + {report}").unwrap();
+        }
+    }
+}
+
+pub fn run_script(path: &str) {
+    let path = Path::new(path);
+    let source = read_to_string(path).unwrap();
+    let expected = Expectations::load(path, &source);
+    let outcome = Outcome::run(path, source);
+
+    let mut failures = String::new();
+    if let Some(expected_stdout) = &expected.stdout {
+        let actual: Vec<&str> = outcome.stdout.lines().collect();
+        if expected_stdout != &actual {
+            writeln!(failures, "stdout did not match (- expected, + actual):").unwrap();
+            failures.push_str(&diff(expected_stdout, &actual));
+        }
+    }
+    compare_reports(&mut failures, "errors", &expected.errors, &outcome.errors);
+    compare_reports(
+        &mut failures,
+        "warnings",
+        &expected.warnings,
+        &outcome.warnings,
+    );
+
+    if !failures.is_empty() {
+        panic!("{}\n{failures}", path.display());
+    }
+}
@@ -1,4 +1,536 @@
 extern crate macros;
 use macros::make_tests;
 
+mod common;
+
+use std::{fmt::Write, fs::read_to_string, io::Cursor, path::Path};
+
+use limesherbet::{
+    formatter::format_source,
+    frontend::{
+        ast::{
+            declaration::Declaration,
+            dump::AstTree,
+            expression::Expression,
+            identifier::Identifier,
+            node::{AstPosition, Node},
+            visit::Visitor,
+            visit_mut::VisitorMut,
+        },
+        resolver::{Resolution, Resolver},
+        scanner::Token,
+        span::LineIndex,
+    },
+    lsp::{read_message, serve, write_message},
+    test_runner::{discover, run_file},
+};
+use serde_json::{json, Value};
+
 make_tests!();
+
+#[test]
+fn test_blocks_run_in_isolation() {
+    let report = run_file(Path::new("tests/scripts/test_blocks/blocks.mng")).unwrap();
+    assert_eq!(report.outcomes.len(), 6);
+    assert_eq!(report.passed(), 5);
+
+    let failed: Vec<_> = report.failed().collect();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].name, "fails");
+    let failure = failed[0].failure.as_ref().unwrap();
+    assert!(
+        failure.starts_with("22:5: assertion failed: double(test) == 7"),
+        "{failure}"
+    );
+    assert!(
+        failure.contains("left: 6") && failure.contains("right: 7"),
+        "{failure}"
+    );
+}
+
+#[test]
+fn doc_comments_attach_to_declarations() {
+    let source = "/// adds one\n/// to x\nfunc inc(x) { return x + 1; }\n\
+                  /// the answer\nlet answer = 42;\nlet plain = 1;";
+    let (parser, nodes) = common::parse(source);
+    assert!(!parser.had_error);
+
+    let docs: Vec<Option<String>> = nodes
+        .iter()
+        .map(|node| match node {
+            Node::Declaration(Declaration::FunctionDeclaration(function)) => function.doc.clone(),
+            Node::Declaration(Declaration::VariableDeclaration(variable)) => variable.doc.clone(),
+            node => panic!("{node:?}"),
+        })
+        .collect();
+    assert_eq!(
+        docs,
+        vec![
+            Some(String::from("adds one\nto x")),
+            Some(String::from("the answer")),
+            None
+        ]
+    );
+}
+
+#[test]
+fn large_files_report_the_right_location() {
+    let mut source = String::new();
+    let mut lines = 0;
+    while source.len() <= u16::MAX as usize * 2 {
+        writeln!(source, "let padding_{lines} = {lines};").unwrap();
+        lines += 1;
+    }
+    source.push_str("let bad = 1 ~ 2;\n");
+    let outcome = common::Outcome::run(Path::new("large.mng"), source.clone());
+    let expected = format!("large.mng:{}:13 - Error at `~`", lines + 1);
+    assert!(
+        outcome.errors.iter().any(|error| error.contains(&expected)),
+        "{:?}",
+        outcome.errors
+    );
+
+    source.truncate(source.len() - "let bad = 1 ~ 2;\n".len());
+    source.push_str("assert_eq padding_0, 1;\n");
+    let outcome = common::Outcome::run(Path::new("large.mng"), source);
+    let expected = format!("{}:1: assertion failed: padding_0 == 1", lines + 1);
+    assert!(
+        outcome.errors.iter().any(|error| error.contains(&expected)),
+        "{:?}",
+        outcome.errors
+    );
+}
+
+#[test]
+fn tokens_stay_small() {
+    // a kind and a span, the text stays in the source
+    assert!(std::mem::size_of::<Token>() <= 12);
+}
+
+#[test]
+fn nodes_span_everything_they_were_parsed_from() {
+    let source = "let total = add(1, 2 * 3);\nif total >= 7 { print total; } else { print 0; }\n\
+                  func add(a, b) { return a + b; }";
+    let (parser, nodes) = common::parse(source);
+    assert!(!parser.had_error);
+
+    let text = |node: &dyn AstPosition| &source[node.span().range()];
+    let texts: Vec<&str> = nodes.iter().map(|node| text(node)).collect();
+    assert_eq!(
+        texts,
+        vec![
+            "let total = add(1, 2 * 3);",
+            "if total >= 7 { print total; } else { print 0; }",
+            "func add(a, b) { return a + b; }",
+        ]
+    );
+
+    let Node::Declaration(Declaration::VariableDeclaration(total)) = &nodes[0] else {
+        panic!("{:?}", nodes[0]);
+    };
+    let Expression::CallExpr(call) = &total.intializer else {
+        panic!("{:?}", total.intializer);
+    };
+    assert_eq!(text(call), "add(1, 2 * 3)");
+    assert_eq!(text(&call.parameters[1]), "2 * 3");
+
+    let Node::Expression(Expression::If(if_expr)) = &nodes[1] else {
+        panic!("{:?}", nodes[1]);
+    };
+    assert_eq!(text(&*if_expr.predicate), "total >= 7");
+    assert_eq!(text(&if_expr.then), "{ print total; }");
+}
+
+#[test]
+fn visitors_reach_every_name() {
+    #[derive(Default)]
+    struct Names(Vec<String>);
+    impl Visitor for Names {
+        fn visit_identifier(&mut self, identifier: &Identifier) {
+            self.0.push(identifier.lexeme.clone());
+        }
+    }
+    struct Rename;
+    impl VisitorMut for Rename {
+        fn visit_identifier(&mut self, identifier: &mut Identifier) {
+            if identifier.lexeme == "n" {
+                identifier.lexeme = String::from("count");
+            }
+        }
+    }
+
+    let source = "func twice(n, step = 1) { return n * 2 + step; }\n\
+                  let [first, rest...] = [twice(3), 4];\n\
+                  print match first { 6 => rest.len(), other if other > 1 => other, _ => 0 };";
+    let (parser, mut nodes) = common::parse(source);
+    assert!(!parser.had_error);
+
+    for node in &mut nodes {
+        Rename.visit_node(node);
+    }
+    let mut names = Names::default();
+    for node in &nodes {
+        names.visit_node(node);
+    }
+    assert_eq!(
+        names.0,
+        vec![
+            "twice", "count", "step", "count", "step", "twice", "first", "rest", "first", "rest",
+            "other", "other", "other"
+        ]
+    );
+}
+
+#[test]
+fn ast_dumps_are_indented_and_json() {
+    let source = "let [a, rest...] = [1, -2];\nprint match a { 1 | 2 => \"low\", _ => rest };";
+    let (parser, nodes) = common::parse(source);
+    assert!(!parser.had_error);
+
+    let tree = AstTree::from_nodes(&nodes);
+    assert_eq!(
+        tree.to_text(&LineIndex::new(source)),
+        "File
+  Let (1:1..1:28)
+    Array (1:20..1:27)
+      Literal 1 (1:21..1:22)
+      Negate (1:24..1:26)
+        Literal 2 (1:25..1:26)
+    Binding array
+      Identifier a (1:6..1:7)
+      Identifier rest (1:9..1:13)
+  Print (2:1..2:45)
+    Match (2:7..2:44)
+      Identifier a (2:13..2:14)
+      MatchArm (2:17..2:31)
+        Pattern alternatives
+          Pattern literal
+            Literal 1
+          Pattern literal
+            Literal 2
+        Literal \"low\" (2:26..2:31)
+      MatchArm (2:33..2:42)
+        Pattern _
+        Identifier rest (2:38..2:42)
+"
+    );
+
+    let json = tree.to_json();
+    assert_eq!(json["kind"], "File");
+    assert_eq!(json["children"][0]["kind"], "Let");
+    assert_eq!(json["children"][0]["span"]["start"], 0);
+    assert_eq!(json["children"][0]["span"]["end"], 27);
+
+    // named arguments keep their labels
+    let source = "f(1, b: 3);\nlist.push(x, at: 0);";
+    let (parser, nodes) = common::parse(source);
+    assert!(!parser.had_error);
+    assert_eq!(
+        AstTree::from_nodes(&nodes).to_text(&LineIndex::new(source)),
+        "File
+  Call (1:1..1:11)
+    Identifier f (1:1..1:2)
+    Literal 1 (1:3..1:4)
+    NamedArgument b (1:6..1:10)
+      Literal 3 (1:9..1:10)
+  Invoke push (2:1..2:20)
+    Identifier list (2:1..2:5)
+    Identifier x (2:11..2:12)
+    NamedArgument at (2:14..2:19)
+      Literal 0 (2:18..2:19)
+"
+    );
+}
+
+#[test]
+fn formatting_every_script_is_stable() {
+    fn shape(source: &str) -> AstTree {
+        fn forget_spans(tree: &mut AstTree) {
+            tree.span = None;
+            tree.children.iter_mut().for_each(forget_spans);
+        }
+        let mut tree = AstTree::from_nodes(&common::parse(source).1);
+        forget_spans(&mut tree);
+        tree
+    }
+
+    let mut formatted_any = false;
+    for path in discover(Path::new("tests/scripts")) {
+        let source = read_to_string(&path).unwrap();
+        // scripts that are meant not to compile can't be formatted
+        let Ok(formatted) = format_source(&path, &source) else {
+            continue;
+        };
+        formatted_any = true;
+        assert_eq!(
+            format_source(&path, &formatted).as_ref(),
+            Ok(&formatted),
+            "formatting {} twice changed it",
+            path.display()
+        );
+        assert_eq!(shape(&formatted), shape(&source), "{}", path.display());
+        // counted by the parser's own scanner, which is the one that decides
+        // what is a comment
+        let comments = |source: &str| common::parse(source).0.scanner.comments.len();
+        assert_eq!(
+            comments(&formatted),
+            comments(&source),
+            "{}",
+            path.display()
+        );
+        assert_eq!(
+            common::Outcome::run(&path, formatted).stdout,
+            common::Outcome::run(&path, source).stdout,
+            "{}",
+            path.display()
+        );
+    }
+    assert!(formatted_any);
+}
+
+#[test]
+fn formatting_puts_comments_back() {
+    let source = "// fruit\n\n\n/// how ripe\nlet   ripe=[1,2 ,3]; // trailing\nfunc pick(x,y=2){\
+                  if x>y{return x;}else{ /* keep */ return y;}\n\n\n// done\n}\n\
+                  print match ripe.len() { // arms\n3=>\"all\" , _ => { print 0; } };\n\
+                  let f = |a, b| a+b;\nif ripe { print 1; } else if f { print 2; } else { print 3; }";
+    assert_eq!(
+        format_source(Path::new("fmt.mng"), source).unwrap(),
+        "// fruit
+
+/// how ripe
+let ripe = [1, 2, 3]; // trailing
+func pick(x, y = 2) {
+    if x > y {
+        return x;
+    } else { /* keep */
+        return y;
+    }
+
+    // done
+}
+print match ripe.len() { // arms
+    3 => \"all\",
+    _ => {
+        print 0;
+    }
+};
+let f = |a, b| a + b;
+if ripe {
+    print 1;
+} else if f {
+    print 2;
+} else {
+    print 3;
+}
+"
+    );
+}
+
+#[test]
+fn language_server_answers_a_scripted_client() {
+    let uri = "file:///scratch.mng";
+    let source = "/// adds two\nfunc add(a, b) {\n    let sum = a + b;\n    return sum;\n}\n\
+                  let total = add(1, 2);\nprint total;\n";
+    let at = |id: u64, method: &str, line: u64, character: u64| {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": {
+                "textDocument": { "uri": uri },
+                "position": { "line": line, "character": character },
+            },
+        })
+    };
+    let script = [
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": uri, "version": 1, "text": source } },
+        }),
+        // `add` in `add(1, 2)` and `sum` in `return sum`
+        at(2, "textDocument/hover", 5, 13),
+        at(3, "textDocument/definition", 3, 12),
+        // `b` in `a + b` is the parameter
+        at(4, "textDocument/definition", 2, 18),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 5,
+            "method": "textDocument/documentSymbol",
+            "params": { "textDocument": { "uri": uri } },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 6,
+            "method": "textDocument/semanticTokens/full",
+            "params": { "textDocument": { "uri": uri } },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": uri, "version": 2 },
+                "contentChanges": [{ "text": "let x = 1;\nlet y = ;\n" }],
+            },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": uri, "version": 3 },
+                "contentChanges": [{ "text": "if x {} else if x {}\nwhile x print x;\n" }],
+            },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": uri, "version": 4 },
+                "contentChanges": [{ "text": "print match 1 { 1 => 2 };\n" }],
+            },
+        }),
+        json!({ "jsonrpc": "2.0", "id": 7, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ];
+    let mut input = Vec::new();
+    for message in &script {
+        write_message(&mut input, message).unwrap();
+    }
+    let mut output = Vec::new();
+    assert!(serve(Cursor::new(input), &mut output).unwrap());
+
+    let mut output = Cursor::new(output);
+    let mut replies = Vec::new();
+    while let Some(reply) = read_message(&mut output).unwrap() {
+        replies.push(reply);
+    }
+    let response = |id: u64| -> &Value {
+        let reply = replies.iter().find(|reply| reply["id"] == id).unwrap();
+        &reply["result"]
+    };
+    let diagnostics: Vec<&Value> = replies
+        .iter()
+        .filter(|reply| reply["method"] == "textDocument/publishDiagnostics")
+        .map(|reply| &reply["params"]["diagnostics"])
+        .collect();
+    let range = |line: u64, start: u64, end: u64| {
+        json!({
+            "start": { "line": line, "character": start },
+            "end": { "line": line, "character": end },
+        })
+    };
+
+    assert_eq!(
+        response(1)["capabilities"]["semanticTokensProvider"]["full"],
+        true
+    );
+    assert_eq!(
+        diagnostics,
+        [
+            &json!([]),
+            &json!([{
+                "range": range(1, 8, 9),
+                "severity": 1,
+                "source": "limesherbet",
+                "message": "Error at `;`: Expected an expression",
+            }]),
+            &json!([{
+                "range": range(1, 8, 13),
+                "severity": 1,
+                "source": "limesherbet",
+                "message": "Error at `print`: Expected '{' after the loop's condition",
+            }]),
+            // the warning covers the whole match
+            &json!([{
+                "range": range(0, 6, 24),
+                "severity": 2,
+                "source": "limesherbet",
+                "message": "match is not exhaustive, add a `_` arm",
+            }])
+        ]
+    );
+
+    assert_eq!(
+        response(2)["contents"]["value"],
+        "```limesherbet\nfunc add(a, b)\n```\nglobal\n\n---\n\nadds two"
+    );
+    assert_eq!(response(3)["range"], range(2, 8, 11));
+    assert_eq!(response(4)["range"], range(1, 12, 13));
+
+    let symbols = response(5).as_array().unwrap();
+    let names: Vec<_> = symbols.iter().map(|symbol| &symbol["name"]).collect();
+    assert_eq!(names, ["add", "total"]);
+    assert_eq!(symbols[0]["children"][0]["name"], "sum");
+    assert_eq!(symbols[0]["selectionRange"], range(1, 5, 8));
+
+    // the first line is the doc comment, then `func` and the declared `add`
+    let data = response(6)["data"].as_array().unwrap();
+    assert_eq!(data[..15], [0, 0, 12, 3, 0, 1, 0, 4, 0, 0, 0, 5, 3, 7, 1]);
+    assert_eq!(data.len() % 5, 0);
+    assert_eq!(response(7), &Value::Null);
+}
+
+#[test]
+fn resolver_gives_names_their_slots() {
+    struct Names(Vec<(String, Resolution)>);
+    impl Visitor for Names {
+        fn visit_identifier(&mut self, identifier: &Identifier) {
+            self.0
+                .push((identifier.lexeme.clone(), identifier.resolution));
+        }
+    }
+
+    let source = "let g = 1;\nfunc outer(p) {\n    let x = p + g;\n    \
+                  func inner() { return x; }\n    return inner;\n}";
+    let (parser, mut nodes) = common::parse(source);
+    let resolver = Resolver::run(&mut nodes, &parser.diagnostics);
+    // functions don't close over locals yet
+    assert!(resolver.had_error);
+
+    let mut names = Names(Vec::new());
+    for node in &nodes {
+        names.visit_node(node);
+    }
+    let expected = [
+        ("g", Resolution::Global),
+        ("outer", Resolution::Global),
+        ("p", Resolution::Local(0)),
+        ("p", Resolution::Local(0)),
+        ("g", Resolution::Global),
+        ("x", Resolution::Local(1)),
+        ("inner", Resolution::Local(2)),
+        // reported, and left as a global
+        ("x", Resolution::Global),
+        ("inner", Resolution::Local(2)),
+    ];
+    let names: Vec<_> = names
+        .0
+        .iter()
+        .map(|(name, resolution)| (name.as_str(), *resolution))
+        .collect();
+    assert_eq!(names, expected);
+    // the body pops `x` and `inner` when it ends, `p` goes with the call
+    let Node::Declaration(Declaration::FunctionDeclaration(outer)) = &nodes[1] else {
+        panic!("{:?}", nodes[1]);
+    };
+    assert_eq!(outer.block.locals, 2);
+
+    let table = resolver.table;
+    let symbol = |name: &str| {
+        table
+            .symbols
+            .iter()
+            .position(|symbol| symbol.name == name)
+            .unwrap()
+    };
+    assert_eq!(table.captures.len(), 1);
+    assert_eq!(table.captures[0].symbol, symbol("x"));
+    // `g` in `p + g` is the global on the first line
+    assert_eq!(table.uses[1].symbol, Some(symbol("g")));
+    let outer = &table.symbols[symbol("outer")];
+    assert_eq!(table.symbols[symbol("x")].parent, Some(symbol("outer")));
+    assert_eq!(&source[outer.signature.range()], "func outer(p) ");
+}